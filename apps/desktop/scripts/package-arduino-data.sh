@@ -1,6 +1,7 @@
 #!/bin/bash
 # Package Arduino AVR core data for bundling with the app
-# This creates a compressed archive of the AVR core for offline use
+# This creates a compressed archive of the AVR core for offline use,
+# with the libraries listed in bundled_libraries.txt
 #
 # Run this script from apps/desktop directory:
 #   ./scripts/package-arduino-data.sh
@@ -20,6 +21,7 @@ echo ""
 BINARIES_DIR="src-tauri/binaries"
 RESOURCES_DIR="src-tauri/resources"
 OUTPUT_DIR="$RESOURCES_DIR/arduino-data"
+LIBRARIES_MANIFEST="$OUTPUT_DIR/bundled_libraries.txt"
 
 # Detect platform
 ARCH=$(uname -m)
@@ -100,6 +102,28 @@ if [ -f "$APP_DATA_DIR/packages/arduino/installed.json" ]; then
     cp "$APP_DATA_DIR/packages/arduino/installed.json" "$OUTPUT_DIR/packages/arduino/"
 fi
 
+# Install and copy the bundled libraries (directories.user/libraries)
+# Manifest lines: <library name>[@<version>]: <headers>
+if [ -f "$LIBRARIES_MANIFEST" ]; then
+    echo -e "${YELLOW}Installing bundled libraries...${NC}"
+    "$CLI_BINARY" --config-file "$APP_DATA_DIR/arduino-cli.yaml" lib update-index
+
+    rm -rf "$OUTPUT_DIR/libraries"
+    mkdir -p "$OUTPUT_DIR/libraries"
+
+    grep -v '^\s*#' "$LIBRARIES_MANIFEST" | cut -d: -f1 | while read -r spec; do
+        [ -z "$spec" ] && continue
+        echo "  - $spec"
+        "$CLI_BINARY" --config-file "$APP_DATA_DIR/arduino-cli.yaml" lib install "$spec" --no-deps
+
+        # arduino-cli names the directory after the library, non [A-Za-z0-9_.-] as _
+        dir=$(echo "${spec%@*}" | sed 's/[^A-Za-z0-9_.-]/_/g')
+        cp -r "$APP_DATA_DIR/libraries/$dir" "$OUTPUT_DIR/libraries/"
+    done
+else
+    echo -e "${YELLOW}No bundled_libraries.txt, skipping libraries${NC}"
+fi
+
 # Create a marker file to indicate bundled data
 echo "arduino:avr" > "$OUTPUT_DIR/bundled_cores.txt"
 echo "$(date -u +%Y-%m-%dT%H:%M:%SZ)" > "$OUTPUT_DIR/bundle_timestamp.txt"
@@ -114,4 +138,4 @@ echo "Contents:"
 ls -lh "$OUTPUT_DIR"
 echo ""
 echo -e "${GREEN}Ready to build!${NC}"
-echo "The AVR core will be bundled with your app for offline use."
+echo "The AVR core and libraries will be bundled with your app for offline use."
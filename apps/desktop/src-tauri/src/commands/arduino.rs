@@ -9,20 +9,20 @@
  */
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::process::Stdio;
-use tauri::{AppHandle, Emitter, Manager};
-use tempfile::TempDir;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
 use tokio::process::Command;
 
-/// Compile/upload progress event payload
-#[derive(Debug, Clone, Serialize)]
-pub struct CompileProgress {
-    pub stage: String,
-    pub percent: u8,
-    pub message: String,
-}
+use super::builds::{build_id, BuildRecord, Builds};
+use super::diagnostics::{CompileFailure, Diagnostic, SourceMapEntry};
+use super::hints::{self, ErrorHint};
+use super::includes::{self, MissingLibrary};
+use super::jobs::{Job, JobKind, JobStage, Jobs, Output};
+use super::libraries;
+use super::report::{CompileReport, DEFAULT_WARNING_PERCENT};
+use super::serial::SerialSessions;
+use super::upload::{UploadProgress, UploadReport};
 
 /// Result from upload operation
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,6 +31,41 @@ pub struct UploadResult {
     pub stage: Option<String>,
     pub message: Option<String>,
     pub error: Option<String>,
+    /// Compiler diagnostics when the compile stage failed
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+    /// Explanations of known compile/upload errors
+    #[serde(default)]
+    pub hints: Vec<ErrorHint>,
+    /// Flash/RAM usage, once the compile stage succeeded
+    #[serde(default)]
+    pub report: Option<CompileReport>,
+    /// Bytes written, verify result and duration, once the upload ran
+    #[serde(default)]
+    pub upload: Option<UploadReport>,
+}
+
+/// Optional settings for compile_code and upload_code
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CompileOptions {
+    /// Project the sketch belongs to, each project + board keeps its build
+    pub project: Option<String>,
+    /// Sketch line ranges of Blockly blocks, to map diagnostics to blocks
+    pub source_map: Option<Vec<SourceMapEntry>>,
+    /// Language of error hints ("en" by default)
+    pub lang: Option<String>,
+    /// Flag flash/RAM usage at or above this percentage (90 by default)
+    pub memory_warning_percent: Option<u8>,
+}
+
+impl CompileOptions {
+    fn lang(&self) -> &str {
+        self.lang.as_deref().unwrap_or("en")
+    }
+
+    fn warning_percent(&self) -> u8 {
+        self.memory_warning_percent.unwrap_or(DEFAULT_WARNING_PERCENT)
+    }
 }
 
 /// Core information
@@ -62,10 +97,9 @@ pub struct CoreStatus {
 pub enum ArduinoError {
     #[error("Arduino CLI not found: {0}")]
     CliNotFound(String),
-    #[error("Compilation failed: {0}")]
-    CompileFailed(String),
+    #[error("Compilation failed: {}", .0.message)]
+    CompileFailed(CompileFailure),
     #[error("Upload failed: {0}")]
-    #[allow(dead_code)]
     UploadFailed(String),
     #[error("Core not installed: {0}")]
     #[allow(dead_code)]
@@ -74,11 +108,25 @@ pub enum ArduinoError {
     CoreInstallFailed(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
-    #[error("Temp directory error: {0}")]
-    TempDirError(String),
     #[error("Shell error: {0}")]
     #[allow(dead_code)]
     ShellError(String),
+    #[error("Job cancelled: {0}")]
+    Cancelled(String),
+    #[error("Timed out after {0}s")]
+    TimedOut(u64),
+    #[error("Job not found: {0}")]
+    JobNotFound(String),
+    #[error("Job already running: {0}")]
+    JobExists(String),
+    #[error("Toolchain error: {0}")]
+    ToolchainError(String),
+    #[error("Build not found: {0}")]
+    BuildNotFound(String),
+    #[error("Library operation failed: {0}")]
+    LibraryError(String),
+    #[error("Missing library for {}", .0.include.header)]
+    MissingLibrary(MissingLibrary),
 }
 
 impl Serialize for ArduinoError {
@@ -86,7 +134,12 @@ impl Serialize for ArduinoError {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        match self {
+            // Keep the raw log as `message`, with the parsed diagnostics next to it
+            ArduinoError::CompileFailed(failure) => failure.serialize(serializer),
+            ArduinoError::MissingLibrary(missing) => missing.serialize(serializer),
+            _ => serializer.serialize_str(&self.to_string()),
+        }
     }
 }
 
@@ -95,7 +148,7 @@ const BUNDLED_CORES: &[&str] = &["arduino:avr"];
 
 /// Get the path to the arduino data directory
 /// Uses app data dir (~/.local/share/com.hduino.app/arduino on Linux)
-fn get_data_dir(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
+pub(crate) fn get_data_dir(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
     let app_data_dir = app
         .path()
         .app_data_dir()
@@ -114,7 +167,7 @@ fn get_data_dir(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
 
 /// Get the config file path for arduino-cli
 /// Creates the config file if it doesn't exist
-fn get_config_path(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
+pub(crate) fn get_config_path(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
     let data_dir = get_data_dir(app)?;
     let config_path = data_dir.join("arduino-cli.yaml");
 
@@ -158,7 +211,7 @@ updater:
 }
 
 /// Get path to sidecar binary for direct process spawning
-fn get_sidecar_path(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
+pub(crate) fn get_sidecar_path(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
     let resource_dir = app
         .path()
         .resource_dir()
@@ -201,18 +254,6 @@ fn get_sidecar_path(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
     ))
 }
 
-/// Emit progress event to frontend
-fn emit_progress(app: &AppHandle, stage: &str, percent: u8, message: &str) {
-    let _ = app.emit(
-        "compile-progress",
-        CompileProgress {
-            stage: stage.to_string(),
-            percent,
-            message: message.to_string(),
-        },
-    );
-}
-
 /// Check if a core is bundled (ships with app)
 fn is_bundled_core(core_id: &str) -> bool {
     BUNDLED_CORES.contains(&core_id)
@@ -229,16 +270,24 @@ fn get_core_from_fqbn(fqbn: &str) -> String {
 }
 
 /// Initialize bundled Arduino data (extract on first run)
-/// This ensures offline support by copying bundled AVR core data
-async fn init_bundled_data(app: &AppHandle) -> Result<(), ArduinoError> {
+/// This ensures offline support by copying bundled AVR core data and the
+/// bundled libraries (to directories.user/libraries)
+async fn init_bundled_data(app: &AppHandle, job: &Job) -> Result<(), ArduinoError> {
     let data_dir = get_data_dir(app)?;
     let avr_core_path = data_dir.join("packages/arduino/hardware/avr");
+    let libraries_dir = data_dir.join("libraries");
+
+    // Copy of the manifest the libraries were last extracted for, so
+    // libraries the user uninstalled aren't brought back on every compile
+    let libraries_marker = data_dir.join("bundled_libraries.txt");
+    let libraries_extracted = std::fs::read_to_string(&libraries_marker)
+        .is_ok_and(|manifest| manifest == libraries::BUNDLED_LIBRARIES);
 
     eprintln!("Checking AVR core at: {:?}", avr_core_path);
 
-    // Check if AVR core already exists
-    if avr_core_path.exists() {
-        eprintln!("AVR core already exists, skipping initialization");
+    // Check if AVR core and libraries already exist
+    if avr_core_path.exists() && libraries_extracted {
+        eprintln!("AVR core and libraries already exist, skipping initialization");
         return Ok(());
     }
 
@@ -257,7 +306,7 @@ async fn init_bundled_data(app: &AppHandle) -> Result<(), ArduinoError> {
     let possible_paths = vec![
         resource_dir.join("resources").join("arduino-data"),
         resource_dir.join("arduino-data"),
-        resource_dir.parent().and_then(|p| Some(p.join("Resources").join("arduino-data"))).unwrap_or_default(),
+        resource_dir.parent().map(|p| p.join("Resources").join("arduino-data")).unwrap_or_default(),
     ];
 
     let mut bundled_data = None;
@@ -284,15 +333,15 @@ async fn init_bundled_data(app: &AppHandle) -> Result<(), ArduinoError> {
     };
 
     eprintln!("Initializing bundled Arduino data from: {:?}", bundled_data);
-    emit_progress(app, "initializing", 10, "Setting up Arduino environment...");
+    job.progress(JobStage::Initializing, 10, "Setting up Arduino environment...");
 
     // Copy bundled data to app data directory
     let bundled_packages = bundled_data.join("packages");
-    if bundled_packages.exists() {
+    if !avr_core_path.exists() && bundled_packages.exists() {
         let target_packages = data_dir.join("packages");
         tokio::fs::create_dir_all(&target_packages).await?;
 
-        emit_progress(app, "initializing", 50, "Installing AVR core...");
+        job.progress(JobStage::Initializing, 50, "Installing AVR core...");
 
         eprintln!("Copying packages from {:?} to {:?}", bundled_packages, target_packages);
         // Copy the entire packages directory
@@ -307,14 +356,33 @@ async fn init_bundled_data(app: &AppHandle) -> Result<(), ArduinoError> {
         copy_dir_recursive(&bundled_index, &target_index).await?;
     }
 
-    emit_progress(app, "initializing", 100, "Arduino environment ready");
+    // Copy the bundled libraries, versions the user installed are kept
+    let bundled_libraries = bundled_data.join("libraries");
+    if !libraries_extracted && bundled_libraries.exists() {
+        job.progress(JobStage::Initializing, 80, "Installing libraries...");
+
+        for library in libraries::bundled_libraries() {
+            let dir = libraries::install_dir_name(&library.name);
+            let (source, target) = (bundled_libraries.join(&dir), libraries_dir.join(&dir));
+            if target.exists() {
+                continue;
+            }
+            if source.exists() {
+                copy_dir_recursive(&source, &target).await?;
+            }
+        }
+
+        tokio::fs::write(&libraries_marker, libraries::BUNDLED_LIBRARIES).await?;
+    }
+
+    job.progress(JobStage::Initializing, 100, "Arduino environment ready");
     eprintln!("Arduino environment initialization complete");
 
     Ok(())
 }
 
 /// Recursively copy directory
-async fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> Result<(), ArduinoError> {
+pub(crate) async fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> Result<(), ArduinoError> {
     tokio::fs::create_dir_all(dst).await?;
 
     let mut entries = tokio::fs::read_dir(src).await?;
@@ -337,30 +405,43 @@ async fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> Result<(), ArduinoE
 // ============================================================================
 
 /// Compile Arduino code without uploading
+/// `job_id` lets the frontend cancel the compile with cancel_job
 #[tauri::command]
 pub async fn compile_code(
     app: AppHandle,
     code: String,
     board: String,
-) -> Result<String, ArduinoError> {
+    options: Option<CompileOptions>,
+    job_id: Option<String>,
+) -> Result<CompileReport, ArduinoError> {
+    let options = options.unwrap_or_default();
+    let job = app.state::<Jobs>().start(&app, job_id)?;
+
     // Initialize bundled data on first run (for offline support)
-    init_bundled_data(&app).await?;
+    init_bundled_data(&app, &job).await.map_err(|e| job.fail(e))?;
 
-    let cli_path = get_sidecar_path(&app)?;
-    let config_path = get_config_path(&app)?;
+    let cli_path = get_sidecar_path(&app).map_err(|e| job.fail(e))?;
+    let config_path = get_config_path(&app).map_err(|e| job.fail(e))?;
 
-    // Create temp directory for sketch
-    let temp_dir = TempDir::new().map_err(|e| ArduinoError::TempDirError(e.to_string()))?;
-    let sketch_dir = temp_dir.path().join("sketch");
-    let build_dir = temp_dir.path().join("build");
+    // Reuse the project's build directory, unchanged objects aren't rebuilt
+    let dirs = app
+        .state::<Builds>()
+        .prepare(&app, options.project.as_deref(), &board)
+        .await
+        .map_err(|e| job.fail(e))?;
+    let (sketch_dir, build_dir) = (dirs.sketch_dir.clone(), dirs.build_dir.clone());
 
-    tokio::fs::create_dir_all(&sketch_dir).await?;
-    tokio::fs::create_dir_all(&build_dir).await?;
+    // A header that isn't installed fails now rather than after a compile
+    if let Some(missing) = includes::preflight(&app, &code, &board).await {
+        return Err(job.fail(ArduinoError::MissingLibrary(missing)));
+    }
 
     let sketch_file = sketch_dir.join("sketch.ino");
-    tokio::fs::write(&sketch_file, &code).await?;
+    tokio::fs::write(&sketch_file, &code)
+        .await
+        .map_err(|e| job.fail(e.into()))?;
 
-    emit_progress(&app, "compiling", 10, "Starting compilation...");
+    job.progress(JobStage::Compiling, 10, "Starting compilation...");
 
     let mut cmd = Command::new(&cli_path);
 
@@ -368,152 +449,309 @@ pub async fn compile_code(
         cmd.arg("--config-file").arg(&config_path);
     }
 
-    let mut child = cmd
-        .args([
-            "compile",
-            "--fqbn",
-            &board,
-            "--output-dir",
-            build_dir.to_str().unwrap(),
-            sketch_dir.to_str().unwrap(),
-            "--verbose",
-        ])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
-
-    let stderr = child.stderr.take().unwrap();
-    let mut reader = BufReader::new(stderr).lines();
-
-    // Collect all stderr output for both progress and error reporting
-    let mut stderr_output = Vec::new();
+    // Share the compiled core between all builds
+    cmd.env("ARDUINO_BUILD_CACHE_PATH", &dirs.cache_dir);
+    cmd.args([
+        "compile",
+        "--fqbn",
+        &board,
+        "--build-path",
+        build_dir.to_str().unwrap(),
+        sketch_dir.to_str().unwrap(),
+        "--verbose",
+    ]);
+
     let mut progress = 10u8;
+    let output = job
+        .run(&mut cmd, JobKind::Compile, |_, output| {
+            let Output::Line(line) = output else {
+                return;
+            };
+            if line.contains("Compiling") {
+                progress = progress.saturating_add(5).min(80);
+                job.progress(JobStage::Compiling, progress, line);
+            } else if line.contains("Linking") {
+                job.progress(JobStage::Linking, 85, "Linking...");
+            }
+        })
+        .await;
 
-    while let Ok(Some(line)) = reader.next_line().await {
-        stderr_output.push(line.clone());
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => return Err(job.fail(e)),
+    };
 
-        if line.contains("Compiling") {
-            progress = progress.saturating_add(5).min(80);
-            emit_progress(&app, "compiling", progress, &line);
-        } else if line.contains("Linking") {
-            emit_progress(&app, "compiling", 85, "Linking...");
-        }
+    if !output.success {
+        job.progress(JobStage::Failed, 0, "Compilation failed");
+        let failure = CompileFailure::new(
+            &output.stderr,
+            &[&sketch_dir, &build_dir],
+            options.source_map.as_deref(),
+            options.lang(),
+        );
+        return Err(match includes::missing_include(&app, &failure).await {
+            Some(include) => ArduinoError::MissingLibrary(MissingLibrary {
+                include,
+                failure: Some(failure),
+            }),
+            None => ArduinoError::CompileFailed(failure),
+        });
     }
 
-    let status = child.wait().await?;
+    job.progress(JobStage::Done, 100, "Compilation complete");
 
-    if !status.success() {
-        // Join all stderr lines to create the full error message
-        let error_msg = stderr_output.join("\n");
-        emit_progress(&app, "compiling", 0, "Compilation failed");
-        return Err(ArduinoError::CompileFailed(error_msg));
-    }
-
-    emit_progress(&app, "compiling", 100, "Compilation complete");
+    let id = build_id(options.project.as_deref(), &board, &code);
+    let report = CompileReport::parse(
+        id.clone(),
+        build_dir.to_string_lossy().to_string(),
+        &output.stdout,
+        options.warning_percent(),
+    );
 
-    let build_path = build_dir.to_string_lossy().to_string();
-    std::mem::forget(temp_dir);
+    let builds = app.state::<Builds>();
+    builds.set_last(build_dir.clone());
+    builds.record(
+        id,
+        BuildRecord {
+            build_dir,
+            fqbn: board,
+            report: report.clone(),
+        },
+    );
 
-    Ok(build_path)
+    Ok(report)
 }
 
 /// Upload code to Arduino board
+/// Skips the compile when the same code was just built for this board;
+/// `job_id` lets the frontend cancel the compile/upload with cancel_job
+/// A header that isn't installed fails with MissingLibrary, as in compile_code
 #[tauri::command]
 pub async fn upload_code(
     app: AppHandle,
     port: String,
     code: String,
     board: String,
+    options: Option<CompileOptions>,
+    job_id: Option<String>,
 ) -> Result<UploadResult, ArduinoError> {
+    let options = options.unwrap_or_default();
+    let job = app.state::<Jobs>().start(&app, job_id)?;
+    let builds = app.state::<Builds>();
+    let id = build_id(options.project.as_deref(), &board, &code);
+
+    if let Some((dirs, record)) = builds.reuse(&app, &id).await.map_err(|e| job.fail(e))? {
+        job.progress(JobStage::Compiling, 50, "Code unchanged, using the previous build");
+        builds.set_last(dirs.build_dir.clone());
+        return upload_build_dir(&app, &job, &port, &board, &dirs.build_dir, record.report, &options).await;
+    }
+
     // Initialize bundled data on first run (for offline support)
-    init_bundled_data(&app).await?;
+    init_bundled_data(&app, &job).await.map_err(|e| job.fail(e))?;
 
-    let cli_path = get_sidecar_path(&app)?;
-    let config_path = get_config_path(&app)?;
+    let cli_path = get_sidecar_path(&app).map_err(|e| job.fail(e))?;
+    let config_path = get_config_path(&app).map_err(|e| job.fail(e))?;
 
-    // Create temp directory for sketch
-    let temp_dir = TempDir::new().map_err(|e| ArduinoError::TempDirError(e.to_string()))?;
-    let sketch_dir = temp_dir.path().join("sketch");
-    let build_dir = temp_dir.path().join("build");
+    // A header that isn't installed fails now rather than after a compile
+    if let Some(missing) = includes::preflight(&app, &code, &board).await {
+        return Err(job.fail(ArduinoError::MissingLibrary(missing)));
+    }
 
-    tokio::fs::create_dir_all(&sketch_dir).await?;
-    tokio::fs::create_dir_all(&build_dir).await?;
+    // Reuse the project's build directory, unchanged objects aren't rebuilt
+    let dirs = builds
+        .prepare(&app, options.project.as_deref(), &board)
+        .await
+        .map_err(|e| job.fail(e))?;
+    let (sketch_dir, build_dir) = (dirs.sketch_dir.clone(), dirs.build_dir.clone());
 
     let sketch_file = sketch_dir.join("sketch.ino");
-    tokio::fs::write(&sketch_file, &code).await?;
+    tokio::fs::write(&sketch_file, &code)
+        .await
+        .map_err(|e| job.fail(e.into()))?;
 
     // === COMPILE PHASE ===
-    emit_progress(&app, "compiling", 5, "Starting compilation...");
+    job.progress(JobStage::Compiling, 5, "Starting compilation...");
 
     let mut compile_cmd = Command::new(&cli_path);
     if config_path.exists() {
         compile_cmd.arg("--config-file").arg(&config_path);
     }
 
-    let compile_output = compile_cmd
-        .args([
-            "compile",
-            "--fqbn",
-            &board,
-            "--output-dir",
-            build_dir.to_str().unwrap(),
-            sketch_dir.to_str().unwrap(),
-        ])
-        .output()
-        .await?;
+    // Share the compiled core between all builds
+    compile_cmd.env("ARDUINO_BUILD_CACHE_PATH", &dirs.cache_dir);
+
+    compile_cmd.args([
+        "compile",
+        "--fqbn",
+        &board,
+        "--build-path",
+        build_dir.to_str().unwrap(),
+        sketch_dir.to_str().unwrap(),
+    ]);
+
+    let compile_output = match job.run(&mut compile_cmd, JobKind::Compile, |_, _| {}).await {
+        Ok(output) => output,
+        Err(e) => return Err(job.fail(e)),
+    };
 
-    if !compile_output.status.success() {
-        let error_msg = String::from_utf8_lossy(&compile_output.stderr).to_string();
-        emit_progress(&app, "compiling", 0, "Compilation failed");
+    if !compile_output.success {
+        let failure = CompileFailure::new(
+            &compile_output.stderr,
+            &[&sketch_dir, &build_dir],
+            options.source_map.as_deref(),
+            options.lang(),
+        );
+        job.progress(JobStage::Failed, 0, "Compilation failed");
+        // Same error as compile_code, with the libraries that provide the header
+        if let Some(include) = includes::missing_include(&app, &failure).await {
+            return Err(ArduinoError::MissingLibrary(MissingLibrary {
+                include,
+                failure: Some(failure),
+            }));
+        }
         return Ok(UploadResult {
             success: false,
             stage: Some("compile".to_string()),
             message: None,
-            error: Some(error_msg),
+            error: Some(failure.message),
+            diagnostics: failure.diagnostics,
+            hints: failure.hints,
+            report: None,
+            upload: None,
         });
     }
 
-    emit_progress(&app, "compiling", 50, "Compilation complete");
+    job.progress(JobStage::Compiling, 50, "Compilation complete");
+
+    let report = CompileReport::parse(
+        id.clone(),
+        build_dir.to_string_lossy().to_string(),
+        &compile_output.stdout,
+        options.warning_percent(),
+    );
+    builds.set_last(build_dir.clone());
+    builds.record(
+        id,
+        BuildRecord {
+            build_dir: build_dir.clone(),
+            fqbn: board.clone(),
+            report: report.clone(),
+        },
+    );
+
+    upload_build_dir(&app, &job, &port, &board, &build_dir, report, &options).await
+}
+
+/// Upload a build made by compile_code or upload_code, without recompiling
+/// `build_id` comes from the CompileReport; fails if the build was replaced
+#[tauri::command]
+pub async fn upload_build(
+    app: AppHandle,
+    build_id: String,
+    port: String,
+    options: Option<CompileOptions>,
+    job_id: Option<String>,
+) -> Result<UploadResult, ArduinoError> {
+    let options = options.unwrap_or_default();
+    let job = app.state::<Jobs>().start(&app, job_id)?;
+    let builds = app.state::<Builds>();
+
+    let (dirs, record) = builds
+        .reuse(&app, &build_id)
+        .await
+        .and_then(|found| found.ok_or(ArduinoError::BuildNotFound(build_id)))
+        .map_err(|e| job.fail(e))?;
+    builds.set_last(dirs.build_dir.clone());
+
+    upload_build_dir(&app, &job, &port, &record.fqbn, &dirs.build_dir, record.report, &options).await
+}
+
+/// Upload phase of upload_code / upload_build
+async fn upload_build_dir(
+    app: &AppHandle,
+    job: &Job,
+    port: &str,
+    board: &str,
+    build_dir: &Path,
+    report: CompileReport,
+    options: &CompileOptions,
+) -> Result<UploadResult, ArduinoError> {
+    let cli_path = get_sidecar_path(app).map_err(|e| job.fail(e))?;
+    let config_path = get_config_path(app).map_err(|e| job.fail(e))?;
 
-    // === UPLOAD PHASE ===
-    emit_progress(&app, "uploading", 55, "Starting upload...");
+    // Take the port from an open serial monitor, it is reopened afterwards
+    let sessions = app.state::<SerialSessions>();
+    let claim = sessions
+        .claim(app, port)
+        .map_err(|e| job.fail(ArduinoError::UploadFailed(e.to_string())))?;
+
+    job.progress(JobStage::Uploading, 55, "Starting upload...");
 
     let mut upload_cmd = Command::new(&cli_path);
     if config_path.exists() {
         upload_cmd.arg("--config-file").arg(&config_path);
     }
 
-    let upload_output = upload_cmd
-        .args([
-            "upload",
-            "--fqbn",
-            &board,
-            "--port",
-            &port,
-            "--input-dir",
-            build_dir.to_str().unwrap(),
-        ])
-        .output()
-        .await?;
+    upload_cmd.args([
+        "upload",
+        "--fqbn",
+        board,
+        "--port",
+        port,
+        "--input-dir",
+        build_dir.to_str().unwrap(),
+        // Without it avrdude runs quiet and prints no progress bars
+        "--verbose",
+    ]);
+
+    let started = Instant::now();
+    let mut progress = UploadProgress::new(55, 99);
+    let upload_output = job
+        .run(&mut upload_cmd, JobKind::Upload, |_, output| {
+            let update = match output {
+                Output::Line(line) => progress.feed(line, true),
+                Output::Partial(text) => progress.feed(text, false),
+            };
+            if let Some((percent, message)) = update {
+                job.progress(JobStage::Uploading, percent, message);
+            }
+        })
+        .await;
+    let upload = progress.finish(started.elapsed());
+
+    sessions.release(app, claim).await;
+    let upload_output = match upload_output {
+        Ok(output) => output,
+        Err(e) => return Err(job.fail(e)),
+    };
 
-    if !upload_output.status.success() {
-        let error_msg = String::from_utf8_lossy(&upload_output.stderr).to_string();
-        emit_progress(&app, "uploading", 0, "Upload failed");
+    if !upload_output.success {
+        let error_msg = upload_output.stderr.join("\n");
+        let hints = hints::explain(&upload_output.stderr, options.lang());
+        job.progress(JobStage::Failed, 0, "Upload failed");
         return Ok(UploadResult {
             success: false,
             stage: Some("upload".to_string()),
             message: None,
             error: Some(error_msg),
+            diagnostics: Vec::new(),
+            hints,
+            report: Some(report),
+            upload: Some(upload),
         });
     }
 
-    emit_progress(&app, "uploading", 100, "Upload complete!");
+    job.progress(JobStage::Done, 100, "Upload complete!");
 
     Ok(UploadResult {
         success: true,
         stage: Some("upload".to_string()),
         message: Some("Code uploaded successfully!".to_string()),
         error: None,
+        diagnostics: Vec::new(),
+        hints: Vec::new(),
+        report: Some(report),
+        upload: Some(upload),
     })
 }
 
@@ -655,12 +893,18 @@ pub async fn check_core_status(app: AppHandle, board_fqbn: String) -> Result<Cor
 }
 
 /// Install an Arduino core
+/// `job_id` lets the frontend cancel the install with cancel_job
 #[tauri::command]
-pub async fn install_core(app: AppHandle, core_id: String) -> Result<String, ArduinoError> {
-    let cli_path = get_sidecar_path(&app)?;
-    let config_path = get_config_path(&app)?;
+pub async fn install_core(
+    app: AppHandle,
+    core_id: String,
+    job_id: Option<String>,
+) -> Result<String, ArduinoError> {
+    let job = app.state::<Jobs>().start(&app, job_id)?;
+    let cli_path = get_sidecar_path(&app).map_err(|e| job.fail(e))?;
+    let config_path = get_config_path(&app).map_err(|e| job.fail(e))?;
 
-    emit_progress(&app, "installing", 10, &format!("Installing {}...", core_id));
+    job.progress(JobStage::Installing, 10, &format!("Installing {}...", core_id));
 
     // First update the index
     let mut update_cmd = Command::new(&cli_path);
@@ -668,12 +912,13 @@ pub async fn install_core(app: AppHandle, core_id: String) -> Result<String, Ard
         update_cmd.arg("--config-file").arg(&config_path);
     }
 
-    let _ = update_cmd
-        .args(["core", "update-index"])
-        .output()
-        .await?;
+    // A failed index update is fine offline, the install reports real errors
+    update_cmd.args(["core", "update-index"]);
+    job.run(&mut update_cmd, JobKind::Install, |_, _| {})
+        .await
+        .map_err(|e| job.fail(e))?;
 
-    emit_progress(&app, "installing", 30, "Downloading core...");
+    job.progress(JobStage::Installing, 30, "Downloading core...");
 
     // Install the core
     let mut install_cmd = Command::new(&cli_path);
@@ -681,17 +926,18 @@ pub async fn install_core(app: AppHandle, core_id: String) -> Result<String, Ard
         install_cmd.arg("--config-file").arg(&config_path);
     }
 
-    let output = install_cmd
-        .args(["core", "install", &core_id])
-        .output()
-        .await?;
+    install_cmd.args(["core", "install", &core_id]);
+    let output = match job.run(&mut install_cmd, JobKind::Install, |_, _| {}).await {
+        Ok(output) => output,
+        Err(e) => return Err(job.fail(e)),
+    };
 
-    if output.status.success() {
-        emit_progress(&app, "installing", 100, "Core installed successfully!");
+    if output.success {
+        job.progress(JobStage::Done, 100, "Core installed successfully!");
         Ok(format!("Successfully installed {}", core_id))
     } else {
-        let error = String::from_utf8_lossy(&output.stderr).to_string();
-        emit_progress(&app, "installing", 0, "Installation failed");
+        let error = output.stderr.join("\n");
+        job.progress(JobStage::Failed, 0, "Installation failed");
         Err(ArduinoError::CoreInstallFailed(error))
     }
 }
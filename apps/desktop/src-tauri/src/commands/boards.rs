@@ -0,0 +1,353 @@
+/*!
+ * Board auto-detection
+ * Maps a serial port's USB VID/PID to likely board FQBNs
+ *
+ * Sources, best first:
+ * - arduino-cli board list (exact match against installed cores)
+ * - Built-in table of official Arduino IDs and common clone USB chips
+ */
+
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::AppHandle;
+use tokio::process::Command;
+
+use super::arduino::{get_config_path, get_data_dir, get_sidecar_path};
+use super::serial;
+
+/// A board that may be connected to a port
+#[derive(Debug, Clone, Serialize)]
+pub struct BoardCandidate {
+    pub fqbn: String,
+    pub name: String,
+    /// 0-100, candidates are returned highest first
+    pub confidence: u8,
+    /// "arduino-cli" or "usb-table"
+    pub source: String,
+}
+
+/// Known USB VID/PID pair and the board it usually belongs to
+struct UsbBoard {
+    vid: &'static str,
+    pid: &'static str,
+    fqbn: &'static str,
+    name: &'static str,
+    confidence: u8,
+}
+
+/// Official Arduino IDs identify the board, clone USB-serial chips only hint at it
+#[rustfmt::skip]
+const USB_BOARDS: &[UsbBoard] = &[
+    // Arduino Uno
+    UsbBoard { vid: "2341", pid: "0043", fqbn: "arduino:avr:uno", name: "Arduino Uno", confidence: 90 },
+    UsbBoard { vid: "2341", pid: "0001", fqbn: "arduino:avr:uno", name: "Arduino Uno", confidence: 90 },
+    UsbBoard { vid: "2A03", pid: "0043", fqbn: "arduino:avr:uno", name: "Arduino Uno", confidence: 90 },
+    UsbBoard { vid: "2341", pid: "0243", fqbn: "arduino:avr:uno", name: "Arduino Uno", confidence: 90 },
+    // Arduino Mega 2560
+    UsbBoard { vid: "2341", pid: "0010", fqbn: "arduino:avr:mega:cpu=atmega2560", name: "Arduino Mega", confidence: 90 },
+    UsbBoard { vid: "2341", pid: "0042", fqbn: "arduino:avr:mega:cpu=atmega2560", name: "Arduino Mega", confidence: 90 },
+    UsbBoard { vid: "2A03", pid: "0010", fqbn: "arduino:avr:mega:cpu=atmega2560", name: "Arduino Mega", confidence: 90 },
+    UsbBoard { vid: "2A03", pid: "0042", fqbn: "arduino:avr:mega:cpu=atmega2560", name: "Arduino Mega", confidence: 90 },
+    // Arduino Leonardo / Micro (bootloader and sketch PIDs)
+    UsbBoard { vid: "2341", pid: "0036", fqbn: "arduino:avr:leonardo", name: "Arduino Leonardo", confidence: 90 },
+    UsbBoard { vid: "2341", pid: "8036", fqbn: "arduino:avr:leonardo", name: "Arduino Leonardo", confidence: 90 },
+    UsbBoard { vid: "2341", pid: "0037", fqbn: "arduino:avr:micro", name: "Arduino Micro", confidence: 90 },
+    UsbBoard { vid: "2341", pid: "8037", fqbn: "arduino:avr:micro", name: "Arduino Micro", confidence: 90 },
+    // CH340: most Nano clones (old bootloader), also Uno/Mega clones and NodeMCU
+    UsbBoard { vid: "1A86", pid: "7523", fqbn: "arduino:avr:nano:cpu=atmega328old", name: "Arduino Nano (Old Bootloader)", confidence: 60 },
+    UsbBoard { vid: "1A86", pid: "7523", fqbn: "arduino:avr:uno", name: "Arduino Uno", confidence: 50 },
+    UsbBoard { vid: "1A86", pid: "7523", fqbn: "arduino:avr:mega:cpu=atmega2560", name: "Arduino Mega", confidence: 40 },
+    UsbBoard { vid: "1A86", pid: "7523", fqbn: "esp8266:esp8266:generic", name: "ESP8266", confidence: 30 },
+    // CH9102: recent ESP32 dev boards
+    UsbBoard { vid: "1A86", pid: "55D4", fqbn: "esp32:esp32:esp32", name: "ESP32", confidence: 60 },
+    // CP2102: ESP32 / ESP8266 dev boards
+    UsbBoard { vid: "10C4", pid: "EA60", fqbn: "esp32:esp32:esp32", name: "ESP32", confidence: 60 },
+    UsbBoard { vid: "10C4", pid: "EA60", fqbn: "esp8266:esp8266:generic", name: "ESP8266", confidence: 50 },
+    // FT232R: older genuine Nano and Pro Mini programmers
+    UsbBoard { vid: "0403", pid: "6001", fqbn: "arduino:avr:nano", name: "Arduino Nano", confidence: 50 },
+    UsbBoard { vid: "0403", pid: "6001", fqbn: "arduino:avr:pro:cpu=16MHzatmega328", name: "Arduino Pro Mini", confidence: 50 },
+];
+
+/// Confidence given to boards matched by arduino-cli
+const CLI_MATCH_CONFIDENCE: u8 = 100;
+
+/// Detect which boards may be connected to a port, most likely first
+#[tauri::command]
+pub async fn detect_board(app: AppHandle, port: String) -> Result<Vec<BoardCandidate>, String> {
+    let info = serial::list_ports(Some(true))
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|p| p.path == port)
+        .ok_or_else(|| format!("Port not found: {}", port))?;
+
+    let mut candidates = cli_matching_boards(&app, &port).await;
+
+    if let (Some(vid), Some(pid)) = (&info.vendor_id, &info.product_id) {
+        for candidate in usb_table_boards(vid, pid) {
+            // Keep the arduino-cli entry when both sources agree
+            if !candidates.iter().any(|c| c.fqbn == candidate.fqbn) {
+                candidates.push(candidate);
+            }
+        }
+    }
+
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.confidence));
+    Ok(candidates)
+}
+
+/// Boards of the built-in table with this VID/PID (hex, any case)
+fn usb_table_boards(vid: &str, pid: &str) -> Vec<BoardCandidate> {
+    USB_BOARDS
+        .iter()
+        .filter(|b| b.vid.eq_ignore_ascii_case(vid) && b.pid.eq_ignore_ascii_case(pid))
+        .map(|board| BoardCandidate {
+            fqbn: board.fqbn.to_string(),
+            name: board.name.to_string(),
+            confidence: board.confidence,
+            source: "usb-table".to_string(),
+        })
+        .collect()
+}
+
+/// Boards arduino-cli matched to the port, empty if the CLI is unavailable
+async fn cli_matching_boards(app: &AppHandle, port: &str) -> Vec<BoardCandidate> {
+    let (Ok(cli_path), Ok(config_path)) = (get_sidecar_path(app), get_config_path(app)) else {
+        return Vec::new();
+    };
+
+    let mut cmd = Command::new(&cli_path);
+    if config_path.exists() {
+        cmd.arg("--config-file").arg(&config_path);
+    }
+
+    let output = match cmd
+        .args(["board", "list", "--discovery-timeout", "1s", "--format", "json"])
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    let json: serde_json::Value = match serde_json::from_slice(&output.stdout) {
+        Ok(json) => json,
+        Err(_) => return Vec::new(),
+    };
+
+    // arduino-cli 1.x wraps the list in "detected_ports", 0.x returns it bare
+    let detected = json
+        .get("detected_ports")
+        .and_then(|v| v.as_array())
+        .or_else(|| json.as_array());
+
+    let mut candidates = Vec::new();
+    for entry in detected.into_iter().flatten() {
+        let address = entry
+            .pointer("/port/address")
+            .or_else(|| entry.get("address"))
+            .and_then(|v| v.as_str());
+        if address != Some(port) {
+            continue;
+        }
+
+        for board in entry
+            .get("matching_boards")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            if let (Some(name), Some(fqbn)) = (
+                board.get("name").and_then(|v| v.as_str()),
+                board.get("fqbn").and_then(|v| v.as_str()),
+            ) {
+                candidates.push(BoardCandidate {
+                    fqbn: fqbn.to_string(),
+                    name: name.to_string(),
+                    confidence: CLI_MATCH_CONFIDENCE,
+                    source: "arduino-cli".to_string(),
+                });
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Property of a board from its core's boards.txt, e.g. "upload.use_1200bps_touch"
+/// The menu options of the FQBN (`cpu=atmega328old`) override the board's own
+pub(crate) fn board_property(app: &AppHandle, fqbn: &str, key: &str) -> Option<String> {
+    let mut parts = fqbn.split(':');
+    let (vendor, arch) = (parts.next()?, parts.next()?);
+
+    let platform_dir = get_data_dir(app)
+        .ok()?
+        .join("packages")
+        .join(vendor)
+        .join("hardware")
+        .join(arch);
+    let versions = std::fs::read_dir(&platform_dir).into_iter().flatten().flatten();
+    for version in versions {
+        let Ok(text) = std::fs::read_to_string(version.path().join("boards.txt")) else {
+            continue;
+        };
+        if let Some(value) = find_property(&text, fqbn, key) {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Look up a board property in the text of a boards.txt
+fn find_property(boards_txt: &str, fqbn: &str, key: &str) -> Option<String> {
+    let mut parts = fqbn.split(':').skip(2);
+    let board = parts.next()?;
+
+    let mut keys: Vec<String> = parts
+        .next()
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|option| option.split_once('='))
+        .map(|(menu, value)| format!("{}.menu.{}.{}.{}", board, menu, value, key))
+        .collect();
+    keys.push(format!("{}.{}", board, key));
+
+    let properties: HashMap<&str, &str> = boards_txt
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .collect();
+    keys.iter()
+        .find_map(|k| properties.get(k.as_str()))
+        .map(|value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Excerpt of arduino:avr 1.8.6 boards.txt
+    const BOARDS_TXT: &str = "\
+# See: https://arduino.github.io/arduino-cli/latest/platform-specification/
+
+menu.cpu=Processor
+
+uno.name=Arduino Uno
+uno.upload.tool=avrdude
+uno.upload.protocol=arduino
+uno.upload.maximum_size=32256
+uno.upload.speed=115200
+
+nano.name=Arduino Nano
+nano.upload.tool=avrdude
+nano.upload.protocol=arduino
+nano.menu.cpu.atmega328=ATmega328P
+nano.menu.cpu.atmega328.upload.maximum_size=30720
+nano.menu.cpu.atmega328.upload.speed=115200
+nano.menu.cpu.atmega328old=ATmega328P (Old Bootloader)
+nano.menu.cpu.atmega328old.upload.maximum_size=30720
+nano.menu.cpu.atmega328old.upload.speed=57600
+
+leonardo.name=Arduino Leonardo
+leonardo.upload.tool=avrdude
+leonardo.upload.protocol=avr109
+leonardo.upload.use_1200bps_touch=true
+leonardo.upload.wait_for_upload_port=true
+";
+
+    #[test]
+    fn reads_board_properties() {
+        assert_eq!(
+            find_property(
+                BOARDS_TXT,
+                "arduino:avr:leonardo",
+                "upload.use_1200bps_touch"
+            )
+            .as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            find_property(BOARDS_TXT, "arduino:avr:uno", "upload.speed").as_deref(),
+            Some("115200")
+        );
+        assert_eq!(
+            find_property(BOARDS_TXT, "arduino:avr:uno", "upload.use_1200bps_touch"),
+            None
+        );
+        assert_eq!(
+            find_property(BOARDS_TXT, "arduino:avr:mega", "upload.speed"),
+            None
+        );
+        assert_eq!(
+            find_property(BOARDS_TXT, "arduino:avr", "upload.speed"),
+            None
+        );
+    }
+
+    #[test]
+    fn menu_options_override_the_board() {
+        assert_eq!(
+            find_property(
+                BOARDS_TXT,
+                "arduino:avr:nano:cpu=atmega328old",
+                "upload.speed"
+            )
+            .as_deref(),
+            Some("57600")
+        );
+        assert_eq!(
+            find_property(BOARDS_TXT, "arduino:avr:nano:cpu=atmega328", "upload.speed").as_deref(),
+            Some("115200")
+        );
+        // Not set by the menu, the board's own value
+        assert_eq!(
+            find_property(
+                BOARDS_TXT,
+                "arduino:avr:nano:cpu=atmega328old",
+                "upload.protocol"
+            )
+            .as_deref(),
+            Some("arduino")
+        );
+    }
+
+    #[test]
+    fn looks_up_official_boards() {
+        let boards = usb_table_boards("2341", "0043");
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0].fqbn, "arduino:avr:uno");
+        assert_eq!(boards[0].confidence, 90);
+        assert_eq!(boards[0].source, "usb-table");
+
+        // serialport reports lowercase hex on some platforms
+        assert_eq!(usb_table_boards("2a03", "0042")[0].name, "Arduino Mega");
+    }
+
+    #[test]
+    fn clone_chips_give_several_candidates() {
+        let fqbns: Vec<String> = usb_table_boards("1A86", "7523")
+            .into_iter()
+            .map(|b| b.fqbn)
+            .collect();
+        assert_eq!(
+            fqbns,
+            [
+                "arduino:avr:nano:cpu=atmega328old",
+                "arduino:avr:uno",
+                "arduino:avr:mega:cpu=atmega2560",
+                "esp8266:esp8266:generic",
+            ]
+        );
+        assert_eq!(
+            usb_table_boards("10C4", "EA60")[0].fqbn,
+            "esp32:esp32:esp32"
+        );
+    }
+
+    #[test]
+    fn unknown_ids_have_no_candidates() {
+        assert!(usb_table_boards("1234", "5678").is_empty());
+        assert!(usb_table_boards("2341", "").is_empty());
+    }
+}
@@ -0,0 +1,465 @@
+/*!
+ * Persistent build directories
+ * Each project + board pair gets its own sketch and build directory under
+ * <app data>/builds/<project>/<fqbn>, so arduino-cli can reuse the objects of
+ * the previous compile. The compiled Arduino core is shared between all
+ * builds through arduino-cli's build cache (<app data>/build-cache)
+ *
+ * Also remembers the last successful build, for the commands that inspect
+ * it (symbol sizes, disassembly...), and a registry of recent successful
+ * builds keyed by a hash of project, FQBN and code, so an upload of code that
+ * was just verified skips the compile
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::OwnedMutexGuard;
+
+use super::arduino::ArduinoError;
+use super::report::CompileReport;
+
+/// Project name used when the frontend doesn't pass one
+const DEFAULT_PROJECT: &str = "default";
+
+/// Successful builds kept in the registry
+const MAX_RECENT_BUILDS: usize = 20;
+
+/// Build metadata file, written in each build root
+const BUILD_INFO_FILE: &str = "build.json";
+
+/// Characters of a project name or FQBN kept in its directory name
+const DIR_NAME_MAX: usize = 40;
+
+/// Contents of build.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BuildInfo {
+    project: String,
+    fqbn: String,
+}
+
+/// A build directory, as listed by list_builds
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildEntry {
+    pub project: String,
+    pub fqbn: String,
+    pub path: String,
+    pub size: u64,
+    /// Last compile, milliseconds since the Unix epoch
+    pub modified_ms: u64,
+}
+
+/// Disk usage of the build directories and the shared core cache
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildsSize {
+    pub builds: u64,
+    pub cache: u64,
+}
+
+/// Result of purge_builds
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeSummary {
+    pub removed: usize,
+    pub freed: u64,
+}
+
+/// Sketch and build directories of one project + board
+/// The directory is locked while this is alive, so two jobs never share it
+pub(crate) struct BuildDir {
+    pub(crate) sketch_dir: PathBuf,
+    pub(crate) build_dir: PathBuf,
+    pub(crate) cache_dir: PathBuf,
+    _lock: OwnedMutexGuard<()>,
+}
+
+/// A successful build in the registry
+#[derive(Debug, Clone)]
+pub(crate) struct BuildRecord {
+    pub(crate) build_dir: PathBuf,
+    pub(crate) fqbn: String,
+    pub(crate) report: CompileReport,
+}
+
+/// Build directories in use, the last build and recent successful builds
+#[derive(Default)]
+pub struct Builds {
+    last: Mutex<Option<PathBuf>>,
+    locks: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
+    /// (build ID, record), oldest first
+    recent: Mutex<Vec<(String, BuildRecord)>>,
+}
+
+impl Builds {
+    /// Record the output directory of a successful compile
+    pub(crate) fn set_last(&self, build_dir: PathBuf) {
+        *self.last.lock().unwrap() = Some(build_dir);
+    }
+
+    /// Output directory of the last successful compile
+    pub(crate) fn last(&self) -> Option<PathBuf> {
+        self.last.lock().unwrap().clone()
+    }
+
+    /// Create (or reuse) the build directory of a project + board and lock it
+    /// Waits if another job is compiling the same project for the same board
+    pub(crate) async fn prepare(
+        &self,
+        app: &AppHandle,
+        project: Option<&str>,
+        fqbn: &str,
+    ) -> Result<BuildDir, ArduinoError> {
+        let project = project.unwrap_or(DEFAULT_PROJECT);
+        let root = builds_root(app)?.join(dir_name(project)).join(dir_name(fqbn));
+
+        let lock = self.lock_for(&root);
+        let lock = lock.lock_owned().await;
+
+        // The compile overwrites whatever build the directory held
+        self.forget(&root);
+
+        let sketch_dir = root.join("sketch");
+        let build_dir = root.join("build");
+        let cache_dir = cache_root(app)?;
+        tokio::fs::create_dir_all(&sketch_dir).await?;
+        tokio::fs::create_dir_all(&build_dir).await?;
+        tokio::fs::create_dir_all(&cache_dir).await?;
+
+        // Rewritten on every compile, its mtime is the build's age
+        let info = BuildInfo {
+            project: project.to_string(),
+            fqbn: fqbn.to_string(),
+        };
+        let info = serde_json::to_vec_pretty(&info).unwrap_or_default();
+        tokio::fs::write(root.join(BUILD_INFO_FILE), info).await?;
+
+        Ok(BuildDir {
+            sketch_dir,
+            build_dir,
+            cache_dir,
+            _lock: lock,
+        })
+    }
+
+    /// Lock the directory of a registered build for an upload
+    /// None if the build is unknown, or was recompiled or purged since
+    pub(crate) async fn reuse(
+        &self,
+        app: &AppHandle,
+        build_id: &str,
+    ) -> Result<Option<(BuildDir, BuildRecord)>, ArduinoError> {
+        let Some(record) = self.lookup(build_id) else {
+            return Ok(None);
+        };
+        let root = record.build_dir.parent().map(Path::to_path_buf).unwrap_or_default();
+        let lock = self.lock_for(&root).lock_owned().await;
+
+        // Recompiled while waiting for the lock, or deleted
+        let Some(record) = self.lookup(build_id).filter(|r| r.build_dir.exists()) else {
+            return Ok(None);
+        };
+
+        let dirs = BuildDir {
+            sketch_dir: root.join("sketch"),
+            build_dir: record.build_dir.clone(),
+            cache_dir: cache_root(app)?,
+            _lock: lock,
+        };
+        Ok(Some((dirs, record)))
+    }
+
+    /// Register a successful build, replacing older builds of its directory
+    pub(crate) fn record(&self, build_id: String, record: BuildRecord) {
+        let mut recent = self.recent.lock().unwrap();
+        recent.retain(|(id, r)| *id != build_id && r.build_dir != record.build_dir);
+        recent.push((build_id, record));
+
+        let excess = recent.len().saturating_sub(MAX_RECENT_BUILDS);
+        recent.drain(..excess);
+    }
+
+    fn lookup(&self, build_id: &str) -> Option<BuildRecord> {
+        self.recent
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(id, _)| id == build_id)
+            .map(|(_, record)| record.clone())
+    }
+
+    /// Drop registered builds under a directory
+    fn forget(&self, root: &Path) {
+        self.recent
+            .lock()
+            .unwrap()
+            .retain(|(_, r)| !r.build_dir.starts_with(root));
+    }
+
+    fn lock_for(&self, root: &Path) -> Arc<tokio::sync::Mutex<()>> {
+        self.locks
+            .lock()
+            .unwrap()
+            .entry(root.to_path_buf())
+            .or_default()
+            .clone()
+    }
+
+    /// Lock a build directory unless a job is using it
+    fn try_lock(&self, root: &Path) -> Option<OwnedMutexGuard<()>> {
+        self.lock_for(root).try_lock_owned().ok()
+    }
+
+    /// Drop the lock of a deleted build directory
+    /// Kept if another job took it meanwhile and is waiting on it
+    fn remove_lock(&self, root: &Path, guard: OwnedMutexGuard<()>) {
+        let mut locks = self.locks.lock().unwrap();
+        // The map and the guard hold the only references
+        if locks.get(root).is_some_and(|lock| Arc::strong_count(lock) == 2) {
+            locks.remove(root);
+        }
+        drop(guard);
+    }
+
+    /// Delete the shared core cache unless a job is using a build
+    /// directory, returns the bytes freed
+    /// The registry stays locked until it's deleted, so a compile starting
+    /// meanwhile waits in prepare
+    fn purge_cache(&self, cache: &Path) -> std::io::Result<u64> {
+        let locks = self.locks.lock().unwrap();
+        // The map holds one reference, jobs holding or waiting for the lock
+        // hold the others
+        if locks.values().any(|lock| Arc::strong_count(lock) > 1) || !cache.exists() {
+            return Ok(0);
+        }
+
+        let size = dir_size(cache);
+        std::fs::remove_dir_all(cache)?;
+        Ok(size)
+    }
+}
+
+/// List the build directories, most recent first
+#[tauri::command]
+pub async fn list_builds(app: AppHandle) -> Result<Vec<BuildEntry>, ArduinoError> {
+    let root = builds_root(&app)?;
+    let mut entries = tauri::async_runtime::spawn_blocking(move || scan_builds(&root))
+        .await
+        .map_err(|e| ArduinoError::IoError(std::io::Error::other(e.to_string())))?;
+
+    entries.sort_by_key(|e| std::cmp::Reverse(e.modified_ms));
+    Ok(entries)
+}
+
+/// Disk usage of the build directories and the shared core cache
+#[tauri::command]
+pub async fn get_builds_size(app: AppHandle) -> Result<BuildsSize, ArduinoError> {
+    let builds = builds_root(&app)?;
+    let cache = cache_root(&app)?;
+
+    tauri::async_runtime::spawn_blocking(move || BuildsSize {
+        builds: dir_size(&builds),
+        cache: dir_size(&cache),
+    })
+    .await
+    .map_err(|e| ArduinoError::IoError(std::io::Error::other(e.to_string())))
+}
+
+/// Delete build directories not compiled in `older_than_days` (all if None)
+/// Builds in use are kept; `include_cache` also clears the shared core cache
+/// when no build is running
+#[tauri::command]
+pub async fn purge_builds(
+    app: AppHandle,
+    builds: State<'_, Builds>,
+    older_than_days: Option<u64>,
+    include_cache: Option<bool>,
+) -> Result<PurgeSummary, ArduinoError> {
+    let root = builds_root(&app)?;
+    let entries = tauri::async_runtime::spawn_blocking(move || scan_builds(&root))
+        .await
+        .map_err(|e| ArduinoError::IoError(std::io::Error::other(e.to_string())))?;
+    let cutoff = older_than_days
+        .map(|days| now_ms().saturating_sub(days * 24 * 60 * 60 * 1000))
+        .unwrap_or(u64::MAX);
+
+    let mut summary = PurgeSummary {
+        removed: 0,
+        freed: 0,
+    };
+
+    for entry in entries {
+        let path = PathBuf::from(&entry.path);
+        if entry.modified_ms > cutoff {
+            continue;
+        }
+        // In use, held until deleted so no compile starts in it meanwhile
+        let Some(guard) = builds.try_lock(&path) else {
+            continue;
+        };
+
+        tokio::fs::remove_dir_all(&path).await?;
+        builds.forget(&path);
+        builds.remove_lock(&path, guard);
+        summary.removed += 1;
+        summary.freed += entry.size;
+
+        // The inspect commands would point at a deleted build
+        let mut last = builds.last.lock().unwrap();
+        if last.as_ref().is_some_and(|l| l.starts_with(&path)) {
+            *last = None;
+        }
+    }
+
+    // Kept while compiles run in any build directory, they use the cache
+    if include_cache.unwrap_or(false) {
+        let cache = cache_root(&app)?;
+        let handle = app.clone();
+        summary.freed += tauri::async_runtime::spawn_blocking(move || {
+            handle.state::<Builds>().purge_cache(&cache)
+        })
+        .await
+        .map_err(|e| ArduinoError::IoError(std::io::Error::other(e.to_string())))??;
+    }
+
+    Ok(summary)
+}
+
+/// ID of a build: hash of everything that changes the compiled binary
+/// arduino-cli gets the same arguments for every build, the board options
+/// (CPU, clock, partition scheme...) are part of the FQBN; CompileOptions
+/// only change how the result is reported, so they aren't hashed
+pub(crate) fn build_id(project: Option<&str>, fqbn: &str, code: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    (project.unwrap_or(DEFAULT_PROJECT), fqbn, code).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// <app data>/builds
+fn builds_root(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
+    Ok(app_data_dir(app)?.join("builds"))
+}
+
+/// <app data>/build-cache, shared by all builds
+fn cache_root(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
+    Ok(app_data_dir(app)?.join("build-cache"))
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
+    app.path().app_data_dir().map_err(|e| {
+        ArduinoError::IoError(std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string()))
+    })
+}
+
+/// Directory name for a project or FQBN, the name with other characters
+/// replaced plus a hash of the original, so "my project" and "my_project"
+/// get their own directory ("arduino:avr:nano" -> "arduino_avr_nano-3919d022")
+fn dir_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .take(DIR_NAME_MAX)
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}-{:08x}", safe, fnv1a(name) as u32)
+}
+
+/// 64-bit FNV-1a; unlike DefaultHasher it can't change with the Rust
+/// version, which would orphan every build directory
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Build directories under builds/<project>/<fqbn> that have a build.json
+fn scan_builds(root: &Path) -> Vec<BuildEntry> {
+    let projects = std::fs::read_dir(root).into_iter().flatten().flatten();
+    let build_roots = projects.flat_map(|p| std::fs::read_dir(p.path()).into_iter().flatten().flatten());
+
+    build_roots
+        .filter_map(|dir| {
+            let path = dir.path();
+            let info_path = path.join(BUILD_INFO_FILE);
+            let info: BuildInfo = serde_json::from_slice(&std::fs::read(&info_path).ok()?).ok()?;
+            let modified = std::fs::metadata(&info_path).and_then(|m| m.modified()).ok()?;
+
+            Some(BuildEntry {
+                project: info.project,
+                fqbn: info.fqbn,
+                size: dir_size(&path),
+                path: path.to_string_lossy().to_string(),
+                modified_ms: modified
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or(Duration::ZERO)
+                    .as_millis() as u64,
+            })
+        })
+        .collect()
+}
+
+/// Total size of the files under a directory, 0 if it doesn't exist
+fn dir_size(path: &Path) -> u64 {
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_names_keep_readable_prefix() {
+        assert_eq!(dir_name("arduino:avr:nano"), "arduino_avr_nano-3919d022");
+        assert!(dir_name("esp32:esp32:esp32:PartitionScheme=huge_app")
+            .starts_with("esp32_esp32_esp32_PartitionScheme_huge_"));
+    }
+
+    #[test]
+    fn similar_names_get_their_own_directory() {
+        assert_ne!(dir_name("my project"), dir_name("my_project"));
+        assert_ne!(dir_name("projet été"), dir_name("projet ôté"));
+        assert_ne!(
+            dir_name("arduino:avr:nano:cpu=atmega328"),
+            dir_name("arduino:avr:nano:cpu=atmega328old")
+        );
+    }
+
+    #[test]
+    fn long_names_are_cut() {
+        let name = "a".repeat(200);
+        assert_eq!(dir_name(&name).len(), DIR_NAME_MAX + 9);
+        assert_ne!(dir_name(&name), dir_name(&"a".repeat(201)));
+    }
+
+    #[test]
+    fn build_id_covers_project_board_and_code() {
+        let id = build_id(None, "arduino:avr:uno", "void setup() {}");
+        assert_eq!(
+            id,
+            build_id(Some(DEFAULT_PROJECT), "arduino:avr:uno", "void setup() {}")
+        );
+        assert_ne!(
+            id,
+            build_id(Some("robot"), "arduino:avr:uno", "void setup() {}")
+        );
+        assert_ne!(id, build_id(None, "arduino:avr:nano", "void setup() {}"));
+        assert_ne!(id, build_id(None, "arduino:avr:uno", "void setup() { }"));
+    }
+}
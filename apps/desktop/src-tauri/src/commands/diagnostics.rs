@@ -0,0 +1,427 @@
+/*!
+ * gcc/g++ diagnostic parsing
+ * Turns compiler output into file/line/column entries the code view can
+ * underline, with arduino-cli's temp sketch paths stripped
+ *
+ * Recognised lines:
+ * - `file:line:col: error|warning|note|fatal error: message`
+ * - `file:line: undefined reference to ...` (linker)
+ *
+ * With a source map from the block generator, sketch diagnostics are also
+ * translated to the IDs of the Blockly blocks that produced the line
+ */
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::hints::{self, ErrorHint};
+
+/// Diagnostic severity, "fatal error" counts as error
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A note attached to a diagnostic ("note: candidate is ...")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticNote {
+    pub file: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+/// One compiler or linker diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// "sketch.ino" for the sketch, other files as reported by the compiler
+    pub file: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub severity: Severity,
+    pub message: String,
+    pub notes: Vec<DiagnosticNote>,
+    /// Blocks that generated the line, innermost first (needs a source map)
+    #[serde(default)]
+    pub block_ids: Vec<String>,
+}
+
+/// Sketch line range (1-based, inclusive) generated by a Blockly block
+/// Ranges of nested blocks overlap, e.g. a loop body inside its loop block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceMapEntry {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub block_id: String,
+}
+
+/// Compile failure, the raw compiler log plus what was parsed from it
+#[derive(Debug, Clone, Serialize)]
+pub struct CompileFailure {
+    pub message: String,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Explanations of known errors, in the requested language
+    pub hints: Vec<ErrorHint>,
+}
+
+impl CompileFailure {
+    pub(crate) fn new(
+        log: &[String],
+        roots: &[&Path],
+        source_map: Option<&[SourceMapEntry]>,
+        lang: &str,
+    ) -> Self {
+        let mut diagnostics = parse_diagnostics(log, roots);
+        if let Some(source_map) = source_map {
+            map_to_blocks(&mut diagnostics, source_map);
+        }
+
+        CompileFailure {
+            message: log.join("\n"),
+            diagnostics,
+            hints: hints::explain(log, lang),
+        }
+    }
+}
+
+/// Sketch file name after clean_path
+const SKETCH_FILE: &str = "sketch.ino";
+
+/// Severity markers, checked in order so "fatal error" wins over "error"
+const MARKERS: &[(&str, Severity)] = &[
+    (": fatal error: ", Severity::Error),
+    (": error: ", Severity::Error),
+    (": warning: ", Severity::Warning),
+    (": note: ", Severity::Note),
+];
+
+/// Parse compiler output, `roots` are the temp sketch/build directories
+/// stripped from paths and messages
+pub(crate) fn parse_diagnostics(log: &[String], roots: &[&Path]) -> Vec<Diagnostic> {
+    let prefixes = root_prefixes(roots);
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for text in log {
+        let Some((location, severity, message)) = split_line(text) else {
+            continue;
+        };
+        let (file, line, column) = parse_location(location);
+        let file = clean_path(file, &prefixes);
+        let message = strip_prefixes(message, &prefixes);
+
+        // Notes explain the diagnostic before them
+        if severity == Severity::Note {
+            if let Some(last) = diagnostics.last_mut() {
+                last.notes.push(DiagnosticNote {
+                    file,
+                    line,
+                    column,
+                    message,
+                });
+                continue;
+            }
+        }
+
+        diagnostics.push(Diagnostic {
+            file,
+            line,
+            column,
+            severity,
+            message,
+            notes: Vec::new(),
+            block_ids: Vec::new(),
+        });
+    }
+
+    diagnostics
+}
+
+/// Fill in `block_ids` of diagnostics located in the sketch
+/// Errors reported inside a library fall back to the sketch line in their
+/// notes ("required from here"), which is where the block's code is
+pub(crate) fn map_to_blocks(diagnostics: &mut [Diagnostic], source_map: &[SourceMapEntry]) {
+    for diagnostic in diagnostics {
+        let own = (diagnostic.file.as_str(), diagnostic.line);
+        let notes = diagnostic.notes.iter().map(|n| (n.file.as_str(), n.line));
+
+        diagnostic.block_ids = std::iter::once(own)
+            .chain(notes)
+            .filter(|(file, _)| *file == SKETCH_FILE)
+            .filter_map(|(_, line)| line)
+            .map(|line| blocks_at(source_map, line))
+            .find(|ids| !ids.is_empty())
+            .unwrap_or_default();
+    }
+}
+
+/// Blocks whose range contains `line`, innermost (shortest range) first
+fn blocks_at(source_map: &[SourceMapEntry], line: u32) -> Vec<String> {
+    let mut hits: Vec<&SourceMapEntry> = source_map
+        .iter()
+        .filter(|e| e.start_line <= line && line <= e.end_line)
+        .collect();
+    hits.sort_by_key(|e| e.end_line.saturating_sub(e.start_line));
+    hits.into_iter().map(|e| e.block_id.clone()).collect()
+}
+
+/// Split a line into (location, severity, message)
+fn split_line(text: &str) -> Option<(&str, Severity, &str)> {
+    let found = MARKERS
+        .iter()
+        .filter_map(|(marker, severity)| text.find(marker).map(|i| (i, marker.len(), *severity)))
+        .min_by_key(|(i, _, _)| *i);
+
+    if let Some((i, len, severity)) = found {
+        return Some((&text[..i], severity, text[i + len..].trim()));
+    }
+
+    // Linker errors have no severity marker
+    let i = text.find(": undefined reference to ")?;
+    Some((&text[..i], Severity::Error, text[i + 2..].trim()))
+}
+
+/// Parse `file:line:col` / `file:line` / `file`
+fn parse_location(location: &str) -> (&str, Option<u32>, Option<u32>) {
+    let Some((rest, last)) = location.rsplit_once(':') else {
+        return (location, None, None);
+    };
+    let Ok(last) = last.parse::<u32>() else {
+        return (location, None, None);
+    };
+
+    match rest.rsplit_once(':') {
+        Some((file, line)) => match line.parse::<u32>() {
+            Ok(line) => (file, Some(line), Some(last)),
+            Err(_) => (rest, Some(last), None),
+        },
+        None => (rest, Some(last), None),
+    }
+}
+
+/// Root directories as path prefixes, longest first, including the
+/// canonical form (macOS reports /private/var for /var temp dirs)
+fn root_prefixes(roots: &[&Path]) -> Vec<String> {
+    let mut prefixes = Vec::new();
+    for root in roots {
+        let canonical = std::fs::canonicalize(root).ok();
+        for path in std::iter::once(root.to_path_buf()).chain(canonical) {
+            let mut prefix = path.to_string_lossy().to_string();
+            prefix.push(std::path::MAIN_SEPARATOR);
+            if !prefixes.contains(&prefix) {
+                prefixes.push(prefix);
+            }
+        }
+    }
+    prefixes.sort_by_key(|p| std::cmp::Reverse(p.len()));
+    prefixes
+}
+
+fn strip_prefixes(text: &str, prefixes: &[String]) -> String {
+    prefixes
+        .iter()
+        .fold(text.to_string(), |text, prefix| text.replace(prefix.as_str(), ""))
+}
+
+/// Strip temp directories, the preprocessed sketch copy maps to the sketch
+fn clean_path(file: &str, prefixes: &[String]) -> String {
+    let file = strip_prefixes(file, prefixes);
+    match file.strip_suffix(".cpp").filter(|f| f.ends_with(".ino")) {
+        Some(ino) => ino.rsplit(['/', '\\']).next().unwrap_or(ino).to_string(),
+        None => file,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Sketch and build directories as compile_code lays them out
+    fn dirs() -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir()
+            .join("hduino-test")
+            .join("arduino_avr_uno");
+        (root.join("sketch"), root.join("build"))
+    }
+
+    fn log(lines: &[String]) -> Vec<Diagnostic> {
+        let (sketch, build) = dirs();
+        parse_diagnostics(lines, &[&sketch, &build])
+    }
+
+    #[test]
+    fn parses_gcc_errors_with_notes() {
+        let (sketch, _) = dirs();
+        let ino = sketch.join("sketch.ino").display().to_string();
+        let lines = vec![
+            format!("{}: In function 'void loop()':", ino),
+            format!(
+                "{}:7:3: error: 'digitalWrit' was not declared in this scope",
+                ino
+            ),
+            "   digitalWrit(13, HIGH);".to_string(),
+            "   ^~~~~~~~~~~".to_string(),
+            format!("{}:7:3: note: suggested alternative: 'digitalWrite'", ino),
+            format!("{}:8:1: error: expected ';' before '}}' token", ino),
+            " }".to_string(),
+            " ^".to_string(),
+            "exit status 1".to_string(),
+        ];
+
+        let diagnostics = log(&lines);
+        assert_eq!(diagnostics.len(), 2);
+
+        let first = &diagnostics[0];
+        assert_eq!(first.file, "sketch.ino");
+        assert_eq!((first.line, first.column), (Some(7), Some(3)));
+        assert_eq!(first.severity, Severity::Error);
+        assert_eq!(
+            first.message,
+            "'digitalWrit' was not declared in this scope"
+        );
+        assert_eq!(first.notes.len(), 1);
+        assert_eq!(
+            first.notes[0].message,
+            "suggested alternative: 'digitalWrite'"
+        );
+
+        assert_eq!(diagnostics[1].message, "expected ';' before '}' token");
+        assert!(diagnostics[1].notes.is_empty());
+    }
+
+    #[test]
+    fn fatal_error_is_an_error() {
+        let (sketch, _) = dirs();
+        let lines = vec![
+            format!(
+                "{}:1:10: fatal error: Servo.h: No such file or directory",
+                sketch.join("sketch.ino").display()
+            ),
+            " #include <Servo.h>".to_string(),
+            "          ^~~~~~~~~".to_string(),
+            "compilation terminated.".to_string(),
+        ];
+
+        let diagnostics = log(&lines);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].message, "Servo.h: No such file or directory");
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].column),
+            (Some(1), Some(10))
+        );
+    }
+
+    #[test]
+    fn preprocessed_sketch_maps_to_the_sketch() {
+        let (_, build) = dirs();
+        let cpp = build.join("sketch").join("sketch.ino.cpp");
+        let lines = vec![format!(
+            "{}:12:7: warning: unused variable 'reading' [-Wunused-variable]",
+            cpp.display()
+        )];
+
+        let diagnostics = log(&lines);
+        assert_eq!(diagnostics[0].file, "sketch.ino");
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(
+            diagnostics[0].message,
+            "unused variable 'reading' [-Wunused-variable]"
+        );
+    }
+
+    #[test]
+    fn strips_build_paths_from_messages() {
+        let (_, build) = dirs();
+        let header = build.join("sketch").join("config.h");
+        let lines = vec![format!(
+            "{}:3:9: warning: \"LED\" redefined [enabled by default]",
+            header.display()
+        )];
+
+        let diagnostics = log(&lines);
+        assert_eq!(
+            diagnostics[0].file,
+            Path::new("sketch").join("config.h").display().to_string()
+        );
+    }
+
+    #[test]
+    fn parses_linker_errors() {
+        let lines = vec![
+            "/tmp/cc8Jm2Zj.ltrans0.ltrans.o: In function `main':".to_string(),
+            "<artificial>:(.text.startup+0x86): undefined reference to `setup'".to_string(),
+            "collect2: error: ld returned 1 exit status".to_string(),
+        ];
+
+        let diagnostics = log(&lines);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].file, "<artificial>:(.text.startup+0x86)");
+        assert_eq!(diagnostics[0].line, None);
+        assert_eq!(diagnostics[0].message, "undefined reference to `setup'");
+        assert_eq!(diagnostics[1].file, "collect2");
+        assert_eq!(diagnostics[1].message, "ld returned 1 exit status");
+    }
+
+    #[test]
+    fn parses_locations() {
+        assert_eq!(
+            parse_location("sketch.ino:7:3"),
+            ("sketch.ino", Some(7), Some(3))
+        );
+        assert_eq!(
+            parse_location("sketch.ino:7"),
+            ("sketch.ino", Some(7), None)
+        );
+        assert_eq!(parse_location("collect2"), ("collect2", None, None));
+        assert_eq!(
+            parse_location("C:\\Temp\\sketch.ino:7:3"),
+            ("C:\\Temp\\sketch.ino", Some(7), Some(3))
+        );
+    }
+
+    #[test]
+    fn maps_sketch_lines_to_blocks() {
+        let source_map = vec![
+            SourceMapEntry {
+                start_line: 4,
+                end_line: 10,
+                block_id: "loop".to_string(),
+            },
+            SourceMapEntry {
+                start_line: 5,
+                end_line: 6,
+                block_id: "if".to_string(),
+            },
+        ];
+        let mut diagnostics =
+            log(&["sketch.ino:5:7: error: 'x' was not declared in this scope".to_string()]);
+        map_to_blocks(&mut diagnostics, &source_map);
+        assert_eq!(diagnostics[0].block_ids, ["if", "loop"]);
+
+        let mut diagnostics = log(&["sketch.ino:2:1: error: 'y' does not name a type".to_string()]);
+        map_to_blocks(&mut diagnostics, &source_map);
+        assert!(diagnostics[0].block_ids.is_empty());
+    }
+
+    #[test]
+    fn library_errors_map_through_their_notes() {
+        let source_map = vec![SourceMapEntry {
+            start_line: 8,
+            end_line: 8,
+            block_id: "servo_write".to_string(),
+        }];
+        let lines = vec![
+            "/opt/libraries/Servo/src/Servo.h:104:8: error: 'int Servo::min' is private"
+                .to_string(),
+            "sketch.ino:8:9: note: within this context".to_string(),
+        ];
+
+        let mut diagnostics = log(&lines);
+        map_to_blocks(&mut diagnostics, &source_map);
+        assert_eq!(diagnostics[0].block_ids, ["servo_write"]);
+    }
+}
@@ -80,3 +80,29 @@ pub async fn open_file_dialog(app: tauri::AppHandle) -> Result<Option<ProjectFil
         None => Ok(None),
     }
 }
+
+/// Pick a library to install with install_local_library, a .zip file or a
+/// directory when `folder` is set
+#[tauri::command]
+pub async fn pick_library_dialog(
+    app: tauri::AppHandle,
+    folder: bool,
+) -> Result<Option<String>, FileError> {
+    let dialog = app.dialog().file();
+    let picked = if folder {
+        dialog.blocking_pick_folder()
+    } else {
+        dialog.add_filter("Arduino Library", &["zip"]).blocking_pick_file()
+    };
+
+    match picked {
+        Some(FilePath::Path(p)) => Ok(Some(p.to_string_lossy().to_string())),
+        Some(FilePath::Url(url)) => {
+            let path = url
+                .to_file_path()
+                .map_err(|_| FileError::ReadError("Invalid file URL".to_string()))?;
+            Ok(Some(path.to_string_lossy().to_string()))
+        }
+        None => Ok(None),
+    }
+}
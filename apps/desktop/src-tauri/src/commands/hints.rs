@@ -0,0 +1,402 @@
+/*!
+ * Error knowledge base
+ * Matches known gcc, linker, avrdude and esptool failures in a compile or
+ * upload log and explains them in words a beginner can act on
+ *
+ * Texts may contain `{name}`, replaced with the first quoted word of the
+ * matching line (the undeclared variable, the missing header...)
+ */
+
+use serde::{Deserialize, Serialize};
+
+/// Explanation and suggested fix for a known failure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorHint {
+    /// Stable ID, e.g. "not-declared"
+    pub id: String,
+    pub explanation: String,
+    pub fix: String,
+}
+
+/// Text in every supported language
+struct Text {
+    en: &'static str,
+    fr: &'static str,
+}
+
+impl Text {
+    /// Region variants ("fr-FR", "fr_CA") use their language's text
+    fn get(&self, lang: &str) -> &'static str {
+        match lang.split(['-', '_']).next().unwrap_or(lang) {
+            "fr" => self.fr,
+            _ => self.en,
+        }
+    }
+}
+
+/// A known failure, matched when any of `patterns` appears in a log line
+struct KnownError {
+    id: &'static str,
+    patterns: &'static [&'static str],
+    explanation: Text,
+    fix: Text,
+}
+
+/// Checked in order, so specific patterns come before generic ones
+const KNOWN_ERRORS: &[KnownError] = &[
+    // === COMPILER ===
+    KnownError {
+        id: "not-declared",
+        patterns: &["was not declared in this scope"],
+        explanation: Text {
+            en: "The program uses '{name}', but it was never created or is misspelled.",
+            fr: "Le programme utilise '{name}', mais il n'a jamais été créé ou il est mal orthographié.",
+        },
+        fix: Text {
+            en: "Create the variable or function '{name}' before using it, or check its spelling.",
+            fr: "Crée la variable ou la fonction '{name}' avant de l'utiliser, ou vérifie son orthographe.",
+        },
+    },
+    KnownError {
+        id: "missing-library",
+        patterns: &[".h: No such file or directory"],
+        explanation: Text {
+            en: "The library that provides '{name}' is not installed.",
+            fr: "La bibliothèque qui fournit '{name}' n'est pas installée.",
+        },
+        fix: Text {
+            en: "Install the library from the library manager, then compile again.",
+            fr: "Installe la bibliothèque depuis le gestionnaire de bibliothèques, puis compile à nouveau.",
+        },
+    },
+    KnownError {
+        id: "missing-semicolon",
+        patterns: &["expected ';' before", "expected ';' at end"],
+        explanation: Text {
+            en: "A line is missing its ';' at the end.",
+            fr: "Il manque un ';' à la fin d'une ligne.",
+        },
+        fix: Text {
+            en: "Add ';' at the end of the line just before the one shown.",
+            fr: "Ajoute ';' à la fin de la ligne juste avant celle indiquée.",
+        },
+    },
+    KnownError {
+        id: "redefinition",
+        patterns: &["redefinition of", "conflicting declaration"],
+        explanation: Text {
+            en: "'{name}' is created twice.",
+            fr: "'{name}' est créé deux fois.",
+        },
+        fix: Text {
+            en: "Remove one of the two, or give one of them another name.",
+            fr: "Supprime l'un des deux, ou donne un autre nom à l'un d'eux.",
+        },
+    },
+    // === LINKER ===
+    KnownError {
+        id: "undefined-reference",
+        patterns: &["undefined reference to"],
+        explanation: Text {
+            en: "The function '{name}' is used but its code was never written.",
+            fr: "La fonction '{name}' est utilisée mais son code n'a jamais été écrit.",
+        },
+        fix: Text {
+            en: "Define the function, or install the library it comes from.",
+            fr: "Définis la fonction, ou installe la bibliothèque d'où elle vient.",
+        },
+    },
+    KnownError {
+        id: "sketch-too-big",
+        patterns: &["Sketch too big", "text section exceeds available space"],
+        explanation: Text {
+            en: "The program is too big for the board's memory.",
+            fr: "Le programme est trop gros pour la mémoire de la carte.",
+        },
+        fix: Text {
+            en: "Remove unused blocks or libraries, or use a board with more memory (e.g. Mega).",
+            fr: "Supprime les blocs ou bibliothèques inutiles, ou utilise une carte avec plus de mémoire (ex. Mega).",
+        },
+    },
+    KnownError {
+        id: "ram-full",
+        patterns: &["data section exceeds available space", "Not enough memory"],
+        explanation: Text {
+            en: "The variables use more RAM than the board has.",
+            fr: "Les variables utilisent plus de RAM que la carte n'en a.",
+        },
+        fix: Text {
+            en: "Use fewer or smaller variables and shorter texts, or wrap texts in F(\"...\").",
+            fr: "Utilise moins de variables, des textes plus courts, ou entoure les textes avec F(\"...\").",
+        },
+    },
+    KnownError {
+        id: "core-missing",
+        patterns: &["Platform not installed", "platform not installed", "Invalid FQBN"],
+        explanation: Text {
+            en: "The support package for this board is not installed.",
+            fr: "Le paquet de support de cette carte n'est pas installé.",
+        },
+        fix: Text {
+            en: "Install the board's core from the board manager, then try again.",
+            fr: "Installe le paquet de la carte depuis le gestionnaire de cartes, puis réessaie.",
+        },
+    },
+    // === UPLOAD ===
+    KnownError {
+        id: "port-permission",
+        patterns: &["Permission denied"],
+        explanation: Text {
+            en: "Your user is not allowed to use the serial port.",
+            fr: "Ton utilisateur n'a pas le droit d'utiliser le port série.",
+        },
+        fix: Text {
+            en: "On Linux, add yourself to the dialout group (sudo usermod -a -G dialout $USER), then log out and back in.",
+            fr: "Sous Linux, ajoute-toi au groupe dialout (sudo usermod -a -G dialout $USER), puis déconnecte-toi et reconnecte-toi.",
+        },
+    },
+    KnownError {
+        id: "port-busy",
+        patterns: &["Device or resource busy", "Access is denied", "Resource busy"],
+        explanation: Text {
+            en: "Another program is using the serial port.",
+            fr: "Un autre programme utilise le port série.",
+        },
+        fix: Text {
+            en: "Close other Arduino programs and serial monitors, then upload again.",
+            fr: "Ferme les autres programmes Arduino et moniteurs série, puis téléverse à nouveau.",
+        },
+    },
+    KnownError {
+        id: "port-missing",
+        patterns: &["can't open device", "could not open port", "No device found on"],
+        explanation: Text {
+            en: "The board is not connected to the selected port.",
+            fr: "La carte n'est pas connectée au port choisi.",
+        },
+        fix: Text {
+            en: "Check the USB cable, plug the board in again and pick its port in the list.",
+            fr: "Vérifie le câble USB, rebranche la carte et choisis son port dans la liste.",
+        },
+    },
+    KnownError {
+        id: "programmer-not-responding",
+        patterns: &[
+            "programmer is not responding",
+            "stk500_getsync()",
+            "stk500v2_ReceiveMessage(): timeout",
+        ],
+        explanation: Text {
+            en: "The board did not answer. The selected board type may be wrong.",
+            fr: "La carte n'a pas répondu. Le type de carte choisi est peut-être faux.",
+        },
+        fix: Text {
+            en: "Check the board type. For a Nano clone, try the \"Nano (Old Bootloader)\" option.",
+            fr: "Vérifie le type de carte. Pour un clone de Nano, essaie l'option \"Nano (Old Bootloader)\".",
+        },
+    },
+    KnownError {
+        id: "wrong-signature",
+        patterns: &["Expected signature", "Invalid device signature"],
+        explanation: Text {
+            en: "The chip on the board is not the one of the selected board type.",
+            fr: "La puce de la carte ne correspond pas au type de carte choisi.",
+        },
+        fix: Text {
+            en: "Select the board that is really connected (Uno, Nano, Mega...).",
+            fr: "Choisis la carte réellement branchée (Uno, Nano, Mega...).",
+        },
+    },
+    KnownError {
+        id: "esp-no-connect",
+        patterns: &["Failed to connect to ESP", "Timed out waiting for packet header"],
+        explanation: Text {
+            en: "The ESP board did not enter upload mode.",
+            fr: "La carte ESP n'est pas passée en mode téléversement.",
+        },
+        fix: Text {
+            en: "Hold the BOOT button while the upload starts, then release it.",
+            fr: "Maintiens le bouton BOOT pendant le début du téléversement, puis relâche-le.",
+        },
+    },
+];
+
+/// Hints for the known failures found in a log, in order of appearance
+/// `lang` is a language code ("en", "fr", "fr-FR"), unknown languages fall
+/// back to English
+pub(crate) fn explain(log: &[String], lang: &str) -> Vec<ErrorHint> {
+    let mut hints: Vec<ErrorHint> = Vec::new();
+
+    for line in log {
+        let Some(known) = KNOWN_ERRORS
+            .iter()
+            .find(|k| k.patterns.iter().any(|p| line.contains(p)))
+        else {
+            continue;
+        };
+        if hints.iter().any(|h| h.id == known.id) {
+            continue;
+        }
+
+        let name = quoted_word(line).or_else(|| header_name(line)).unwrap_or_default();
+        hints.push(ErrorHint {
+            id: known.id.to_string(),
+            explanation: known.explanation.get(lang).replace("{name}", name),
+            fix: known.fix.get(lang).replace("{name}", name),
+        });
+    }
+
+    hints
+}
+
+/// First word quoted with '...', ‘...’ or `...', as gcc and ld print them
+fn quoted_word(line: &str) -> Option<&str> {
+    let start = line.find(['\'', '‘', '`'])?;
+    let open = line[start..].chars().next()?;
+    let rest = &line[start + open.len_utf8()..];
+    let end = rest.find(['\'', '’'])?;
+    Some(&rest[..end])
+}
+
+/// Header of a "fatal error: Servo.h: No such file or directory" line
+pub(crate) fn header_name(line: &str) -> Option<&str> {
+    let end = line.find(".h: No such file")? + 2;
+    let start = line[..end].rfind(' ').map_or(0, |i| i + 1);
+    Some(&line[start..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(log: &[&str]) -> Vec<String> {
+        log.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn explains_known_errors() {
+        let cases: &[(&str, &str, &str)] = &[
+            (
+                "/tmp/sketch/sketch.ino:7:3: error: 'ledPin' was not declared in this scope",
+                "not-declared",
+                "The program uses 'ledPin', but it was never created or is misspelled.",
+            ),
+            (
+                "/tmp/sketch/sketch.ino:1:10: fatal error: Servo.h: No such file or directory",
+                "missing-library",
+                "The library that provides 'Servo.h' is not installed.",
+            ),
+            (
+                "/tmp/sketch/sketch.ino:8:1: error: expected ';' before '}' token",
+                "missing-semicolon",
+                "A line is missing its ';' at the end.",
+            ),
+            (
+                "<artificial>:(.text.startup+0x86): undefined reference to `setup'",
+                "undefined-reference",
+                "The function 'setup' is used but its code was never written.",
+            ),
+            (
+                "avrdude: stk500_recv(): programmer is not responding",
+                "programmer-not-responding",
+                "The board did not answer. The selected board type may be wrong.",
+            ),
+            (
+                "avrdude: ser_open(): can't open device \"/dev/ttyUSB0\": Permission denied",
+                "port-permission",
+                "Your user is not allowed to use the serial port.",
+            ),
+            (
+                "A fatal error occurred: Failed to connect to ESP32: Timed out waiting for packet header",
+                "esp-no-connect",
+                "The ESP board did not enter upload mode.",
+            ),
+        ];
+
+        for (line, id, explanation) in cases {
+            let hints = explain(&lines(&[line]), "en");
+            assert_eq!(hints.len(), 1, "{}", line);
+            assert_eq!(hints[0].id, *id);
+            assert_eq!(hints[0].explanation, *explanation);
+        }
+    }
+
+    #[test]
+    fn one_hint_per_error_in_log_order() {
+        let hints = explain(
+            &lines(&[
+                "sketch.ino:5:3: error: 'a' was not declared in this scope",
+                "sketch.ino:6:3: error: 'b' was not declared in this scope",
+                "avrdude: stk500_getsync() attempt 1 of 10: not in sync: resp=0x00",
+                "exit status 1",
+            ]),
+            "en",
+        );
+        let ids: Vec<&str> = hints.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, ["not-declared", "programmer-not-responding"]);
+        assert_eq!(
+            hints[0].fix,
+            "Create the variable or function 'a' before using it, or check its spelling."
+        );
+    }
+
+    #[test]
+    fn explains_in_french() {
+        let log = lines(&["sketch.ino:7:3: error: 'ledPin' was not declared in this scope"]);
+        let expected = "Le programme utilise 'ledPin', mais il n'a jamais été créé ou il est mal orthographié.";
+
+        assert_eq!(explain(&log, "fr")[0].explanation, expected);
+        assert_eq!(explain(&log, "fr-FR")[0].explanation, expected);
+        assert_eq!(explain(&log, "fr_CA")[0].explanation, expected);
+    }
+
+    #[test]
+    fn unknown_languages_fall_back_to_english() {
+        let log = lines(&["avrdude: stk500_recv(): programmer is not responding"]);
+        let expected = "The board did not answer. The selected board type may be wrong.";
+
+        assert_eq!(explain(&log, "de")[0].explanation, expected);
+        assert_eq!(explain(&log, "")[0].explanation, expected);
+    }
+
+    #[test]
+    fn unknown_errors_have_no_hint() {
+        let log = lines(&[
+            "collect2: error: ld returned 1 exit status",
+            "exit status 1",
+        ]);
+        assert!(explain(&log, "en").is_empty());
+    }
+
+    #[test]
+    fn finds_first_quoted_word() {
+        assert_eq!(quoted_word("error: 'x' was not declared"), Some("x"));
+        assert_eq!(
+            quoted_word("error: ‘x’ was not declared, did you mean ‘y’"),
+            Some("x")
+        );
+        assert_eq!(quoted_word("undefined reference to `loop'"), Some("loop"));
+        assert_eq!(quoted_word("expected ';' before '}' token"), Some(";"));
+        assert_eq!(quoted_word("exit status 1"), None);
+    }
+
+    #[test]
+    fn finds_header_name() {
+        assert_eq!(
+            header_name("sketch.ino:1:10: fatal error: Servo.h: No such file or directory"),
+            Some("Servo.h")
+        );
+        assert_eq!(
+            header_name("fatal error: Adafruit_SSD1306.h: No such file or directory"),
+            Some("Adafruit_SSD1306.h")
+        );
+        assert_eq!(
+            header_name("Servo.h: No such file or directory"),
+            Some("Servo.h")
+        );
+        assert_eq!(
+            header_name("fatal error: vector: No such file or directory"),
+            None
+        );
+    }
+}
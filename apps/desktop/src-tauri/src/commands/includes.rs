@@ -0,0 +1,252 @@
+/*!
+ * Preflight include resolution
+ * Finds the `#include` directives of a sketch that no installed library or
+ * core provides, with the libraries that would provide them, so the
+ * frontend can offer to install them; compile_code and upload_code run it
+ * before starting arduino-cli
+ *
+ * Headers with a path (`avr/io.h`), without extension (`<vector>`) and the
+ * C standard headers come with the toolchain and are not checked
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+use super::arduino::{get_data_dir, ArduinoError};
+use super::diagnostics::CompileFailure;
+use super::hints;
+use super::libraries;
+
+/// C headers of the toolchains' libc (avr-libc, newlib)
+const STANDARD_HEADERS: &[&str] = &[
+    "alloca.h", "assert.h", "ctype.h", "errno.h", "float.h", "inttypes.h", "iso646.h",
+    "limits.h", "locale.h", "math.h", "setjmp.h", "signal.h", "stdarg.h", "stdbool.h",
+    "stddef.h", "stdint.h", "stdio.h", "stdlib.h", "string.h", "strings.h", "time.h",
+];
+
+/// A header no installed library or core provides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingInclude {
+    pub header: String,
+    /// Libraries that provide it, bundled ones first
+    pub candidates: Vec<String>,
+}
+
+/// Compile failure caused by a header that isn't installed
+/// Serialized with `"kind": "missing-library"` next to the failure's fields
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename = "missing-library")]
+pub struct MissingLibrary {
+    #[serde(flatten)]
+    pub include: MissingInclude,
+    /// The compile log, diagnostics and hints, as for any compile failure
+    /// None when the preflight found the header before compiling
+    #[serde(flatten)]
+    pub failure: Option<CompileFailure>,
+}
+
+/// Check the sketch's includes against the libraries and core installed
+/// for `board`, returns the headers that would fail to compile
+#[tauri::command]
+pub async fn check_includes(
+    app: AppHandle,
+    code: String,
+    board: String,
+) -> Result<Vec<MissingInclude>, ArduinoError> {
+    missing_includes(&app, &code, &board).await
+}
+
+/// Preflight of compile_code and upload_code: the first header of `code`
+/// that isn't installed, None if all are or the libraries can't be listed
+/// (the compile then reports what is really missing)
+pub(crate) async fn preflight(app: &AppHandle, code: &str, board: &str) -> Option<MissingLibrary> {
+    let include = missing_includes(app, code, board).await.ok()?.into_iter().next()?;
+    Some(MissingLibrary {
+        include,
+        failure: None,
+    })
+}
+
+async fn missing_includes(
+    app: &AppHandle,
+    code: &str,
+    board: &str,
+) -> Result<Vec<MissingInclude>, ArduinoError> {
+    let headers: Vec<String> = scan_includes(code)
+        .into_iter()
+        .filter(|h| !is_toolchain_header(h))
+        .collect();
+    if headers.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut provided: HashSet<String> = libraries::installed_libraries(app, Some(board))
+        .await
+        .map_err(ArduinoError::LibraryError)?
+        .into_iter()
+        .flat_map(|lib| lib.includes)
+        .collect();
+    provided.extend(core_headers(app, board)?);
+
+    let mut missing = Vec::new();
+    for header in headers {
+        if provided.contains(&header) {
+            continue;
+        }
+        let candidates = candidates(app, &header).await;
+        missing.push(MissingInclude { header, candidates });
+    }
+
+    Ok(missing)
+}
+
+/// The header a compile failed on ("fatal error: X.h: No such file or
+/// directory"), with the libraries that provide it
+pub(crate) async fn missing_include(
+    app: &AppHandle,
+    failure: &CompileFailure,
+) -> Option<MissingInclude> {
+    let header = failure
+        .message
+        .lines()
+        .filter(|line| line.contains("fatal error:"))
+        .find_map(hints::header_name)?
+        .to_string();
+
+    let candidates = candidates(app, &header).await;
+    Some(MissingInclude { header, candidates })
+}
+
+/// Libraries that provide `header`: the bundled ones, then the library index
+async fn candidates(app: &AppHandle, header: &str) -> Vec<String> {
+    let mut names: Vec<String> = libraries::bundled_libraries()
+        .into_iter()
+        .filter(|lib| lib.includes.iter().any(|h| h == header))
+        .map(|lib| lib.name)
+        .collect();
+
+    // The index is searched by name, then filtered on what the library provides
+    let query = header.strip_suffix(".h").unwrap_or(header).to_string();
+    let found = libraries::search_libraries(app.clone(), query)
+        .await
+        .unwrap_or_default();
+    for lib in found {
+        if lib.includes.iter().any(|h| h == header) && !names.contains(&lib.name) {
+            names.push(lib.name);
+        }
+    }
+
+    names
+}
+
+/// Headers of the `#include` directives, in order, without duplicates
+fn scan_includes(code: &str) -> Vec<String> {
+    let mut headers: Vec<String> = Vec::new();
+
+    for line in code.lines() {
+        let Some(rest) = line.trim_start().strip_prefix('#') else {
+            continue;
+        };
+        let Some(rest) = rest.trim_start().strip_prefix("include") else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let close = match rest.chars().next() {
+            Some('<') => '>',
+            Some('"') => '"',
+            _ => continue,
+        };
+        let Some((header, _)) = rest[1..].split_once(close) else {
+            continue;
+        };
+
+        let header = header.trim().to_string();
+        if !header.is_empty() && !headers.contains(&header) {
+            headers.push(header);
+        }
+    }
+
+    headers
+}
+
+fn is_toolchain_header(header: &str) -> bool {
+    header.contains('/') || !header.contains('.') || STANDARD_HEADERS.contains(&header)
+}
+
+/// Headers of the board's core and variants (Arduino.h, HardwareSerial.h...)
+fn core_headers(app: &AppHandle, fqbn: &str) -> Result<HashSet<String>, ArduinoError> {
+    let mut parts = fqbn.split(':');
+    let (Some(vendor), Some(arch)) = (parts.next(), parts.next()) else {
+        return Ok(HashSet::new());
+    };
+    let platform_dir = get_data_dir(app)?
+        .join("packages")
+        .join(vendor)
+        .join("hardware")
+        .join(arch);
+
+    let mut headers = HashSet::new();
+    let versions = std::fs::read_dir(&platform_dir).into_iter().flatten().flatten();
+    for version in versions {
+        for kind in ["cores", "variants"] {
+            let dirs = std::fs::read_dir(version.path().join(kind)).into_iter().flatten().flatten();
+            for dir in dirs {
+                let files = std::fs::read_dir(dir.path()).into_iter().flatten().flatten();
+                headers.extend(
+                    files
+                        .map(|f| f.file_name().to_string_lossy().to_string())
+                        .filter(|name| name.ends_with(".h")),
+                );
+            }
+        }
+    }
+
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_includes_in_order() {
+        let code = r#"#include <Servo.h>
+#include "DHT.h"
+  #  include   <Wire.h>
+#include <Servo.h>
+
+Servo servo;
+
+void setup() {
+  servo.attach(9);
+}
+"#;
+        assert_eq!(scan_includes(code), ["Servo.h", "DHT.h", "Wire.h"]);
+    }
+
+    #[test]
+    fn skips_other_directives_and_comments() {
+        let code = r#"#define LED 13
+#ifdef ESP32
+#include <WiFi.h>
+#endif
+// #include <Adafruit_NeoPixel.h>
+#include <
+#include <LiquidCrystal_I2C.h // unclosed
+#include_next <stdio.h>
+"#;
+        assert_eq!(scan_includes(code), ["WiFi.h"]);
+    }
+
+    #[test]
+    fn toolchain_headers_are_not_checked() {
+        assert!(is_toolchain_header("avr/pgmspace.h"));
+        assert!(is_toolchain_header("freertos/FreeRTOS.h"));
+        assert!(is_toolchain_header("vector"));
+        assert!(is_toolchain_header("math.h"));
+        assert!(is_toolchain_header("stdint.h"));
+        assert!(!is_toolchain_header("Servo.h"));
+        assert!(!is_toolchain_header("Adafruit_SSD1306.h"));
+    }
+}
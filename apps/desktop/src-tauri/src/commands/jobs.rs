@@ -0,0 +1,429 @@
+/*!
+ * Job registry for long-running arduino-cli operations
+ * Every compile/upload/install gets a job ID that can be cancelled, and each
+ * process it runs is killed (with its children) on cancel or timeout
+ *
+ * Progress is reported with "job-progress" events tagged with the job ID, so
+ * concurrent operations (two windows, an install during a compile) stay apart
+ *
+ * Every output line of a job process is also sent as a "build-log" event,
+ * for the verbose output console
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::sync::watch;
+
+use super::arduino::ArduinoError;
+
+/// What a job process does, selects its timeout
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum JobKind {
+    Compile,
+    Upload,
+    Install,
+}
+
+/// Stage of a job, sent with every progress event
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStage {
+    Initializing,
+    Compiling,
+    Linking,
+    Uploading,
+    Installing,
+    Done,
+    Failed,
+}
+
+/// Job progress event payload ("job-progress")
+#[derive(Debug, Clone, Serialize)]
+pub struct JobProgress {
+    pub job_id: String,
+    pub stage: JobStage,
+    pub percent: u8,
+    pub message: String,
+}
+
+/// Output stream of a job process
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Output line event payload ("build-log")
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildLog {
+    pub job_id: String,
+    pub stream: Stream,
+    pub line: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+/// What a job process printed
+pub(crate) enum Output<'a> {
+    /// A complete line, ended by \n or the end of the stream
+    Line(&'a str),
+    /// The current line so far, or as last redrawn after a \r, e.g. a
+    /// progress bar being drawn
+    Partial(&'a str),
+}
+
+/// Per-process timeouts in seconds, 0 disables the timeout
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct JobTimeouts {
+    pub compile_secs: u64,
+    pub upload_secs: u64,
+    pub install_secs: u64,
+}
+
+impl Default for JobTimeouts {
+    fn default() -> Self {
+        JobTimeouts {
+            compile_secs: 300,
+            upload_secs: 120,
+            install_secs: 1800,
+        }
+    }
+}
+
+impl JobTimeouts {
+    fn for_kind(&self, kind: JobKind) -> Option<Duration> {
+        let secs = match kind {
+            JobKind::Compile => self.compile_secs,
+            JobKind::Upload => self.upload_secs,
+            JobKind::Install => self.install_secs,
+        };
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+/// Collected output of a finished job process
+pub(crate) struct ProcessOutput {
+    pub(crate) success: bool,
+    pub(crate) stdout: Vec<String>,
+    pub(crate) stderr: Vec<String>,
+}
+
+/// Registry of running jobs, keyed by job ID
+#[derive(Default)]
+pub struct Jobs {
+    running: Mutex<HashMap<String, Arc<watch::Sender<bool>>>>,
+    timeouts: Mutex<JobTimeouts>,
+    next_id: AtomicU64,
+}
+
+impl Jobs {
+    /// Register a job, using the frontend's ID if it picked one
+    /// The job is unregistered when the returned guard is dropped; fails if
+    /// a job with the same ID is still running
+    pub(crate) fn start(
+        &self,
+        app: &AppHandle,
+        job_id: Option<String>,
+    ) -> Result<Job, ArduinoError> {
+        let id = job_id
+            .unwrap_or_else(|| format!("job-{}", self.next_id.fetch_add(1, Ordering::SeqCst)));
+        let (cancel, cancelled) = watch::channel(false);
+        let cancel = Arc::new(cancel);
+
+        match self.running.lock().unwrap().entry(id.clone()) {
+            Entry::Occupied(_) => return Err(ArduinoError::JobExists(id)),
+            Entry::Vacant(entry) => {
+                entry.insert(cancel.clone());
+            }
+        }
+
+        Ok(Job {
+            app: app.clone(),
+            id,
+            timeouts: *self.timeouts.lock().unwrap(),
+            _cancel: cancel,
+            cancelled,
+        })
+    }
+}
+
+/// A running job, see Jobs::start
+pub(crate) struct Job {
+    app: AppHandle,
+    id: String,
+    timeouts: JobTimeouts,
+    /// Keeps the channel open while the job runs
+    _cancel: Arc<watch::Sender<bool>>,
+    cancelled: watch::Receiver<bool>,
+}
+
+impl Job {
+    /// Emit a progress event for this job
+    pub(crate) fn progress(&self, stage: JobStage, percent: u8, message: &str) {
+        let _ = self.app.emit(
+            "job-progress",
+            JobProgress {
+                job_id: self.id.clone(),
+                stage,
+                percent,
+                message: message.to_string(),
+            },
+        );
+    }
+
+    /// Emit a Failed event for an error that ends the job, and return it
+    pub(crate) fn fail(&self, error: ArduinoError) -> ArduinoError {
+        self.progress(JobStage::Failed, 0, &error.to_string());
+        error
+    }
+
+    /// Emit an output line of this job
+    fn log(&self, stream: Stream, line: &str) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let _ = self.app.emit(
+            "build-log",
+            BuildLog {
+                job_id: self.id.clone(),
+                stream,
+                line: line.to_string(),
+                timestamp_ms,
+            },
+        );
+    }
+
+    /// Run a process to completion, reading stdout and stderr concurrently
+    /// `on_output` sees every line as it arrives, and partial lines as they
+    /// grow; lines are also sent as "build-log" events. The process tree is
+    /// killed if the job is cancelled or the timeout for `kind` expires
+    pub(crate) async fn run(
+        &self,
+        cmd: &mut Command,
+        kind: JobKind,
+        mut on_output: impl FnMut(Stream, Output<'_>),
+    ) -> Result<ProcessOutput, ArduinoError> {
+        if *self.cancelled.borrow() {
+            return Err(ArduinoError::Cancelled(self.id.clone()));
+        }
+
+        // Own process group, so the whole tree (avrdude, gcc...) can be killed
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        let mut child = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let pid = child.id();
+
+        let mut stdout = child.stdout.take().unwrap();
+        let mut stderr = child.stderr.take().unwrap();
+        let mut stdout_lines = LineBuffer::new(Stream::Stdout);
+        let mut stderr_lines = LineBuffer::new(Stream::Stderr);
+        let (mut stdout_buf, mut stderr_buf) = ([0u8; 4096], [0u8; 4096]);
+        let (mut stdout_done, mut stderr_done) = (false, false);
+        let mut forward = |stream: Stream, output: Output<'_>| {
+            if let Output::Line(line) = output {
+                self.log(stream, line);
+            }
+            on_output(stream, output);
+        };
+        let mut output = ProcessOutput {
+            success: false,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        };
+
+        let timeout = self.timeouts.for_kind(kind);
+        let deadline = tokio::time::sleep(timeout.unwrap_or(Duration::MAX));
+        tokio::pin!(deadline);
+        let mut cancelled = self.cancelled.clone();
+
+        let result = loop {
+            tokio::select! {
+                read = stdout.read(&mut stdout_buf), if !stdout_done => match read {
+                    Ok(n) if n > 0 => {
+                        stdout_lines.feed(&stdout_buf[..n], &mut output.stdout, &mut forward);
+                    }
+                    _ => {
+                        stdout_lines.finish(&mut output.stdout, &mut forward);
+                        stdout_done = true;
+                    }
+                },
+                read = stderr.read(&mut stderr_buf), if !stderr_done => match read {
+                    Ok(n) if n > 0 => {
+                        stderr_lines.feed(&stderr_buf[..n], &mut output.stderr, &mut forward);
+                    }
+                    _ => {
+                        stderr_lines.finish(&mut output.stderr, &mut forward);
+                        stderr_done = true;
+                    }
+                },
+                status = child.wait(), if stdout_done && stderr_done => {
+                    output.success = status?.success();
+                    break Ok(output);
+                }
+                _ = cancelled.wait_for(|c| *c) => {
+                    break Err(ArduinoError::Cancelled(self.id.clone()));
+                }
+                _ = &mut deadline, if timeout.is_some() => {
+                    break Err(ArduinoError::TimedOut(timeout.unwrap_or_default().as_secs()));
+                }
+            }
+        };
+
+        if result.is_err() {
+            if let Some(pid) = pid {
+                kill_tree(pid).await;
+            }
+            let _ = child.kill().await;
+        }
+
+        result
+    }
+}
+
+/// Splits a process stream into lines as bytes arrive
+struct LineBuffer {
+    stream: Stream,
+    pending: Vec<u8>,
+    /// Last line break was a \r: the line is redrawn, the next byte starts it over
+    redraw: bool,
+}
+
+impl LineBuffer {
+    fn new(stream: Stream) -> Self {
+        LineBuffer {
+            stream,
+            pending: Vec::new(),
+            redraw: false,
+        }
+    }
+
+    /// Hand complete lines to `on_output` and collect them in `lines`, then
+    /// report what is left of the current line
+    /// A \r redraws the line (progress bars), it is only reported as partial
+    fn feed(
+        &mut self,
+        data: &[u8],
+        lines: &mut Vec<String>,
+        on_output: &mut impl FnMut(Stream, Output<'_>),
+    ) {
+        for &byte in data {
+            match byte {
+                b'\n' => self.end_line(lines, on_output),
+                b'\r' => {
+                    if !self.redraw {
+                        self.partial(on_output);
+                    }
+                    self.redraw = true;
+                }
+                _ => {
+                    if self.redraw {
+                        self.pending.clear();
+                        self.redraw = false;
+                    }
+                    self.pending.push(byte);
+                }
+            }
+        }
+
+        if !self.redraw {
+            self.partial(on_output);
+        }
+    }
+
+    /// End of stream, the last line may have no line break
+    fn finish(&mut self, lines: &mut Vec<String>, on_output: &mut impl FnMut(Stream, Output<'_>)) {
+        if !self.pending.is_empty() {
+            self.end_line(lines, on_output);
+        }
+    }
+
+    fn end_line(&mut self, lines: &mut Vec<String>, on_output: &mut impl FnMut(Stream, Output<'_>)) {
+        let line = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending.clear();
+        self.redraw = false;
+        on_output(self.stream, Output::Line(&line));
+        lines.push(line);
+    }
+
+    fn partial(&self, on_output: &mut impl FnMut(Stream, Output<'_>)) {
+        if !self.pending.is_empty() {
+            on_output(self.stream, Output::Partial(&String::from_utf8_lossy(&self.pending)));
+        }
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        self.app
+            .state::<Jobs>()
+            .running
+            .lock()
+            .unwrap()
+            .remove(&self.id);
+    }
+}
+
+/// Cancel a running compile/upload/install job
+#[tauri::command]
+pub fn cancel_job(jobs: State<'_, Jobs>, job_id: String) -> Result<(), ArduinoError> {
+    match jobs.running.lock().unwrap().get(&job_id) {
+        Some(cancel) => {
+            cancel.send_replace(true);
+            Ok(())
+        }
+        None => Err(ArduinoError::JobNotFound(job_id)),
+    }
+}
+
+/// IDs of the running jobs
+#[tauri::command]
+pub fn list_jobs(jobs: State<'_, Jobs>) -> Vec<String> {
+    jobs.running.lock().unwrap().keys().cloned().collect()
+}
+
+/// Get the per-process timeouts
+#[tauri::command]
+pub fn get_job_timeouts(jobs: State<'_, Jobs>) -> JobTimeouts {
+    *jobs.timeouts.lock().unwrap()
+}
+
+/// Set the per-process timeouts, applies to jobs started afterwards
+#[tauri::command]
+pub fn set_job_timeouts(jobs: State<'_, Jobs>, timeouts: JobTimeouts) {
+    *jobs.timeouts.lock().unwrap() = timeouts;
+}
+
+/// Kill a process and everything it spawned (arduino-cli -> avrdude / gcc)
+async fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    let mut cmd = {
+        let mut cmd = Command::new("kill");
+        cmd.args(["-KILL", "--", &format!("-{}", pid)]);
+        cmd
+    };
+
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = Command::new("taskkill");
+        cmd.args(["/T", "/F", "/PID", &pid.to_string()]);
+        cmd
+    };
+
+    let _ = cmd.status().await;
+}
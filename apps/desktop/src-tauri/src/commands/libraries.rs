@@ -0,0 +1,565 @@
+/*!
+ * Arduino library manager
+ * Lists, searches, installs, upgrades and uninstalls libraries with
+ * `arduino-cli lib ...`, using the app's arduino-cli config file
+ *
+ * Libraries go to <arduino data dir>/libraries (directories.user), installs
+ * report progress through the job events like install_core
+ *
+ * The libraries the block generators include ship with the app, listed in
+ * resources/arduino-data/bundled_libraries.txt and extracted on first run
+ *
+ * Vendor libraries that aren't in the index install from a local .zip or
+ * directory, copied to the same place without arduino-cli
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tokio::process::Command;
+
+use super::arduino::{copy_dir_recursive, get_config_path, get_data_dir, get_sidecar_path, ArduinoError};
+use super::jobs::{Job, JobKind, JobStage, Jobs};
+
+/// Library info
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryInfo {
+    pub name: String,
+    /// Installed version, empty if not installed
+    pub installed: String,
+    /// Latest version in the index, empty if unknown
+    pub latest: String,
+    pub author: String,
+    /// One-line description
+    pub sentence: String,
+    /// Headers the library provides ("Servo.h")
+    pub includes: Vec<String>,
+}
+
+/// Result of installing a library from a local .zip or directory
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalLibrary {
+    pub name: String,
+    /// From library.properties, empty without one
+    pub version: String,
+    pub includes: Vec<String>,
+    /// Directory the library is (or would be) installed to
+    pub path: String,
+    /// Version already installed, None if the library is new
+    pub previous: Option<String>,
+    /// False when a version was already installed and `replace` wasn't set
+    pub installed: bool,
+}
+
+/// A library that ships with the app
+#[derive(Debug, Clone, Serialize)]
+pub struct BundledLibrary {
+    pub name: String,
+    /// Pinned version, None when the latest was bundled
+    pub version: Option<String>,
+    pub includes: Vec<String>,
+}
+
+/// Bundled library manifest, `<library name>[@<version>]: <headers>` lines
+pub(crate) const BUNDLED_LIBRARIES: &str = include_str!("../../resources/arduino-data/bundled_libraries.txt");
+
+/// Parse the bundled library manifest
+pub(crate) fn bundled_libraries() -> Vec<BundledLibrary> {
+    BUNDLED_LIBRARIES
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (spec, headers) = line.split_once(':').unwrap_or((line, ""));
+            let (name, version) = match spec.trim().rsplit_once('@') {
+                Some((name, version)) => (name, Some(version.to_string())),
+                None => (spec.trim(), None),
+            };
+            BundledLibrary {
+                name: name.to_string(),
+                version,
+                includes: headers
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|h| !h.is_empty())
+                    .map(|h| h.to_string())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Directory arduino-cli installs a library to, under directories.user/libraries
+pub(crate) fn install_dir_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "_.-".contains(c) { c } else { '_' })
+        .collect()
+}
+
+/// Get list of bundled libraries
+#[tauri::command]
+pub fn get_bundled_libraries() -> Vec<BundledLibrary> {
+    bundled_libraries()
+}
+
+/// List installed libraries
+#[tauri::command]
+pub async fn list_libraries(app: AppHandle) -> Result<Vec<LibraryInfo>, String> {
+    installed_libraries(&app, None).await
+}
+
+/// Installed libraries; with a board, only those it can use, including the
+/// ones its core ships (Wire, SPI...)
+pub(crate) async fn installed_libraries(
+    app: &AppHandle,
+    fqbn: Option<&str>,
+) -> Result<Vec<LibraryInfo>, String> {
+    let mut cmd = cli_command(app).map_err(|e| e.to_string())?;
+    cmd.args(["lib", "list", "--format", "json"]);
+    if let Some(fqbn) = fqbn {
+        cmd.args(["--all", "--fqbn", fqbn]);
+    }
+
+    let output = cmd.output().await.map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+
+    // arduino-cli 1.x: { "installed_libraries": [{ "library": {...}, "release": {...} }] }
+    let mut libraries = Vec::new();
+    if let Some(arr) = json.get("installed_libraries").and_then(|v| v.as_array()) {
+        for item in arr {
+            let Some(library) = item.get("library") else {
+                continue;
+            };
+            let Some(name) = library.get("name").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let installed = str_field(library, "version");
+
+            // `release` is the newest version in the index, when there is one
+            let latest = item
+                .get("release")
+                .and_then(|r| r.get("version"))
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| installed.clone());
+
+            libraries.push(LibraryInfo {
+                name: name.to_string(),
+                installed,
+                latest,
+                author: str_field(library, "author"),
+                sentence: str_field(library, "sentence"),
+                includes: includes(library),
+            });
+        }
+    }
+    libraries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(libraries)
+}
+
+/// Search the library index
+#[tauri::command]
+pub async fn search_libraries(app: AppHandle, query: String) -> Result<Vec<LibraryInfo>, String> {
+    let output = cli_command(&app)
+        .map_err(|e| e.to_string())?
+        .args(["lib", "search", &query, "--omit-releases-details", "--format", "json"])
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+
+    // Search results don't say what is installed
+    let installed: HashMap<String, String> = list_libraries(app)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|lib| (lib.name, lib.installed))
+        .collect();
+
+    // arduino-cli 1.x: { "libraries": [{ "name": "...", "latest": {...} }] }
+    let mut libraries = Vec::new();
+    if let Some(arr) = json.get("libraries").and_then(|v| v.as_array()) {
+        for item in arr {
+            let (Some(name), Some(latest)) = (
+                item.get("name").and_then(|v| v.as_str()),
+                item.get("latest"),
+            ) else {
+                continue;
+            };
+
+            libraries.push(LibraryInfo {
+                name: name.to_string(),
+                installed: installed.get(name).cloned().unwrap_or_default(),
+                latest: str_field(latest, "version"),
+                author: str_field(latest, "author"),
+                sentence: str_field(latest, "sentence"),
+                includes: includes(latest),
+            });
+        }
+    }
+
+    Ok(libraries)
+}
+
+/// Install a library from the index, `version` defaults to the latest
+#[tauri::command]
+pub async fn install_library(
+    app: AppHandle,
+    name: String,
+    version: Option<String>,
+    job_id: Option<String>,
+) -> Result<String, ArduinoError> {
+    let job = app.state::<Jobs>().start(&app, job_id)?;
+    let spec = match &version {
+        Some(version) => format!("{}@{}", name, version),
+        None => name.clone(),
+    };
+
+    job.progress(JobStage::Installing, 10, &format!("Installing {}...", spec));
+    update_index(&app, &job).await?;
+
+    job.progress(JobStage::Installing, 30, "Downloading library...");
+    run_lib(&app, &job, &["install", &spec]).await?;
+
+    job.progress(JobStage::Done, 100, "Library installed successfully!");
+    Ok(format!("Successfully installed {}", spec))
+}
+
+/// Upgrade a library to its latest version, or every library if `name` is None
+#[tauri::command]
+pub async fn upgrade_library(
+    app: AppHandle,
+    name: Option<String>,
+    job_id: Option<String>,
+) -> Result<String, ArduinoError> {
+    let job = app.state::<Jobs>().start(&app, job_id)?;
+    let target = name.as_deref().unwrap_or("all libraries");
+
+    job.progress(JobStage::Installing, 10, &format!("Upgrading {}...", target));
+    update_index(&app, &job).await?;
+
+    job.progress(JobStage::Installing, 30, "Downloading updates...");
+    let mut args = vec!["upgrade"];
+    args.extend(name.as_deref());
+    run_lib(&app, &job, &args).await?;
+
+    job.progress(JobStage::Done, 100, "Libraries upgraded successfully!");
+    Ok(format!("Successfully upgraded {}", target))
+}
+
+/// Uninstall a library
+#[tauri::command]
+pub async fn uninstall_library(
+    app: AppHandle,
+    name: String,
+    job_id: Option<String>,
+) -> Result<String, ArduinoError> {
+    let job = app.state::<Jobs>().start(&app, job_id)?;
+
+    job.progress(JobStage::Installing, 30, &format!("Uninstalling {}...", name));
+    run_lib(&app, &job, &["uninstall", &name]).await?;
+
+    job.progress(JobStage::Done, 100, "Library uninstalled successfully!");
+    Ok(format!("Successfully uninstalled {}", name))
+}
+
+/// Install a library from a local .zip file or directory
+/// An installed version of the same library is only replaced with `replace`,
+/// otherwise it is reported in `previous` and nothing is installed
+#[tauri::command]
+pub async fn install_local_library(
+    app: AppHandle,
+    path: String,
+    replace: bool,
+) -> Result<LocalLibrary, ArduinoError> {
+    let source = PathBuf::from(&path);
+    let data_dir = get_data_dir(&app)?;
+    let libraries_dir = data_dir.join("libraries");
+
+    if !source.is_dir() && !source.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip")) {
+        return Err(ArduinoError::LibraryError(format!(
+            "{} is not a .zip file or a directory",
+            path
+        )));
+    }
+
+    // ZIP files are extracted to a staging directory first
+    let staging = if source.is_dir() {
+        None
+    } else {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let staging = data_dir.join("staging").join(format!("library-{}", millis));
+        let (zip, target) = (source.clone(), staging.clone());
+        let extracted = tauri::async_runtime::spawn_blocking(move || extract_zip(&zip, &target))
+            .await
+            .map_err(|e| ArduinoError::LibraryError(e.to_string()))?;
+        if let Err(e) = extracted {
+            let _ = tokio::fs::remove_dir_all(&staging).await;
+            return Err(e);
+        }
+        Some(staging)
+    };
+
+    let result = match library_root(staging.as_deref().unwrap_or(&source)) {
+        Some(root) => {
+            // Without library.properties the library is named after its
+            // folder, or after the ZIP when its files are at the root
+            let folder = if Some(root.as_path()) == staging.as_deref() {
+                source.file_stem()
+            } else {
+                root.file_name()
+            };
+            let fallback_name = folder.map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            install_from(&root, &fallback_name, &libraries_dir, replace).await
+        }
+        None => Err(ArduinoError::LibraryError(format!(
+            "No library.properties or header file found in {}",
+            path
+        ))),
+    };
+
+    if let Some(staging) = staging {
+        let _ = tokio::fs::remove_dir_all(&staging).await;
+    }
+    result
+}
+
+/// arduino-cli command with the app's config file
+fn cli_command(app: &AppHandle) -> Result<Command, ArduinoError> {
+    let cli_path = get_sidecar_path(app)?;
+    let config_path = get_config_path(app)?;
+
+    let mut cmd = Command::new(&cli_path);
+    if config_path.exists() {
+        cmd.arg("--config-file").arg(&config_path);
+    }
+    Ok(cmd)
+}
+
+/// Refresh the library index
+/// A failed update is fine offline, the install reports real errors
+async fn update_index(app: &AppHandle, job: &Job) -> Result<(), ArduinoError> {
+    let mut cmd = cli_command(app).map_err(|e| job.fail(e))?;
+    cmd.args(["lib", "update-index"]);
+    job.run(&mut cmd, JobKind::Install, |_, _| {})
+        .await
+        .map_err(|e| job.fail(e))?;
+    Ok(())
+}
+
+/// Run `arduino-cli lib <args>` as part of a job
+async fn run_lib(app: &AppHandle, job: &Job, args: &[&str]) -> Result<(), ArduinoError> {
+    let mut cmd = cli_command(app).map_err(|e| job.fail(e))?;
+    cmd.arg("lib").args(args);
+
+    let output = match job.run(&mut cmd, JobKind::Install, |_, _| {}).await {
+        Ok(output) => output,
+        Err(e) => return Err(job.fail(e)),
+    };
+
+    if !output.success {
+        job.progress(JobStage::Failed, 0, "Library operation failed");
+        return Err(ArduinoError::LibraryError(output.stderr.join("\n")));
+    }
+
+    Ok(())
+}
+
+/// Copy a library to directories.user/libraries, see install_local_library
+async fn install_from(
+    root: &Path,
+    fallback_name: &str,
+    libraries_dir: &Path,
+    replace: bool,
+) -> Result<LocalLibrary, ArduinoError> {
+    let properties = read_properties(root);
+    let name = properties
+        .get("name")
+        .cloned()
+        .unwrap_or_else(|| fallback_name.to_string());
+
+    // "", "." and ".." would make the target the libraries dir or its parent
+    let dir_name = install_dir_name(name.trim());
+    if matches!(dir_name.as_str(), "" | "." | "..") {
+        return Err(ArduinoError::LibraryError(format!("Invalid library name '{}'", name)));
+    }
+    let includes = match properties.get("includes") {
+        Some(includes) => includes
+            .split(',')
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .map(|h| h.to_string())
+            .collect(),
+        None => headers(root),
+    };
+
+    let mut target = libraries_dir.join(dir_name);
+    let installed = installed_copy(libraries_dir, &name, &target);
+    if let Some((dir, _)) = &installed {
+        target = dir.clone();
+    }
+
+    let mut library = LocalLibrary {
+        name,
+        version: properties.get("version").cloned().unwrap_or_default(),
+        includes,
+        path: target.to_string_lossy().to_string(),
+        previous: installed.as_ref().map(|(_, version)| version.clone()),
+        installed: false,
+    };
+
+    if library.previous.is_some() {
+        if !replace {
+            return Ok(library);
+        }
+
+        // Picking the installed copy itself would delete the source
+        let same = match (std::fs::canonicalize(root), std::fs::canonicalize(&target)) {
+            (Ok(root), Ok(target)) => root.starts_with(&target) || target.starts_with(&root),
+            _ => false,
+        };
+        if same {
+            return Err(ArduinoError::LibraryError(format!(
+                "{} is already installed from this directory",
+                library.name
+            )));
+        }
+        if target.parent() != Some(libraries_dir) {
+            return Err(ArduinoError::LibraryError(format!(
+                "Refusing to replace {}, it is not in {}",
+                target.display(),
+                libraries_dir.display()
+            )));
+        }
+        tokio::fs::remove_dir_all(&target).await?;
+    }
+
+    copy_dir_recursive(&root.to_path_buf(), &target).await?;
+    library.installed = true;
+    Ok(library)
+}
+
+/// Extract a ZIP file, entries with unsafe paths are rejected by the zip crate
+fn extract_zip(zip: &Path, target: &Path) -> Result<(), ArduinoError> {
+    let invalid = |e: zip::result::ZipError| ArduinoError::LibraryError(format!("Invalid ZIP file: {}", e));
+
+    let file = std::fs::File::open(zip)?;
+    let mut archive = zip::ZipArchive::new(file).map_err(invalid)?;
+    std::fs::create_dir_all(target)?;
+    archive.extract(target).map_err(invalid)
+}
+
+/// Library directory inside an extracted ZIP or picked directory
+/// Vendor ZIPs usually wrap the library in one folder ("Foo-master/")
+fn library_root(dir: &Path) -> Option<PathBuf> {
+    let mut dir = dir.to_path_buf();
+
+    for _ in 0..3 {
+        if dir.join("library.properties").is_file() || !headers(&dir).is_empty() {
+            return Some(dir);
+        }
+
+        let entries: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .ok()?
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                !name.starts_with('.') && name != "__MACOSX"
+            })
+            .map(|e| e.path())
+            .collect();
+        match entries.as_slice() {
+            [only] if only.is_dir() => dir = only.clone(),
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+/// Headers of a library, in src/ for the 1.5 layout, at the root otherwise
+fn headers(root: &Path) -> Vec<String> {
+    let src = root.join("src");
+    let dir = if src.is_dir() { src } else { root.to_path_buf() };
+
+    let mut headers: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".h") || name.ends_with(".hpp"))
+        .collect();
+    headers.sort();
+    headers
+}
+
+/// `key=value` lines of library.properties, empty if there is none
+fn read_properties(root: &Path) -> HashMap<String, String> {
+    std::fs::read_to_string(root.join("library.properties"))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Installed copy of a library, its directory and version
+/// Matched on the directory it would install to, or the name in a
+/// library.properties (copies installed by hand as "Foo-master")
+fn installed_copy(libraries_dir: &Path, name: &str, target: &Path) -> Option<(PathBuf, String)> {
+    let version = |dir: &Path| read_properties(dir).get("version").cloned().unwrap_or_default();
+
+    if target.is_dir() {
+        return Some((target.to_path_buf(), version(target)));
+    }
+
+    std::fs::read_dir(libraries_dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|dir| dir.is_dir())
+        .find(|dir| read_properties(dir).get("name").is_some_and(|n| n == name))
+        .map(|dir| {
+            let version = version(&dir);
+            (dir, version)
+        })
+}
+
+fn str_field(value: &serde_json::Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+fn includes(value: &serde_json::Value) -> Vec<String> {
+    value
+        .get("provides_includes")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
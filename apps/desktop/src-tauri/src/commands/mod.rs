@@ -1,3 +1,15 @@
 pub mod arduino;
+pub mod boards;
+pub mod builds;
+pub mod diagnostics;
 pub mod files;
+pub mod hints;
+pub mod includes;
+pub mod jobs;
+pub mod libraries;
+pub mod plotter;
+pub mod recording;
+pub mod report;
 pub mod serial;
+pub mod toolchain;
+pub mod upload;
@@ -0,0 +1,344 @@
+/*!
+ * Serial plotter pipeline
+ * Parses numeric lines from a monitor session into named series and emits
+ * batched, down-sampled "serial-plot" frames at a fixed rate
+ *
+ * Recognised line formats (same as the Arduino IDE plotter):
+ * - `1.5,2,3` / `1.5 2 3` / tab-separated values, named "value 1", "value 2"...
+ * - `temp:21.5,humidity:40` label:value pairs
+ */
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use super::serial::{SerialError, SerialSessions};
+
+/// Plotter options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlotterSettings {
+    /// Frames emitted per second
+    #[serde(default = "default_rate_hz")]
+    pub rate_hz: u32,
+    /// Points kept per series in each frame, extra samples are decimated
+    #[serde(default = "default_max_points")]
+    pub max_points: usize,
+}
+
+fn default_rate_hz() -> u32 {
+    30
+}
+
+fn default_max_points() -> usize {
+    200
+}
+
+impl Default for PlotterSettings {
+    fn default() -> Self {
+        PlotterSettings {
+            rate_hz: default_rate_hz(),
+            max_points: default_max_points(),
+        }
+    }
+}
+
+/// One series in a plot frame
+#[derive(Debug, Clone, Serialize)]
+pub struct PlotSeries {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+/// Batch of samples received since the previous frame ("serial-plot" event)
+#[derive(Debug, Clone, Serialize)]
+pub struct PlotFrame {
+    pub port: String,
+    /// Numeric lines received in this frame, before decimation
+    pub samples: usize,
+    pub series: Vec<PlotSeries>,
+}
+
+/// Accumulates parsed lines between frames
+pub(crate) struct Plotter {
+    settings: PlotterSettings,
+    interval: Duration,
+    last_frame: Instant,
+    samples: usize,
+    /// Series in first-seen order
+    series: Vec<PlotSeries>,
+}
+
+impl Plotter {
+    pub(crate) fn new(settings: PlotterSettings) -> Self {
+        let interval = Duration::from_millis(1000 / u64::from(settings.rate_hz.clamp(1, 120)));
+        Plotter {
+            settings,
+            interval,
+            last_frame: Instant::now(),
+            samples: 0,
+            series: Vec::new(),
+        }
+    }
+
+    /// Add a received line, non-numeric lines are ignored
+    pub(crate) fn push_line(&mut self, line: &str) {
+        let Some(values) = parse_line(line) else {
+            return;
+        };
+
+        self.samples += 1;
+        for (name, value) in values {
+            match self.series.iter_mut().find(|s| s.name == name) {
+                Some(series) => series.values.push(value),
+                None => self.series.push(PlotSeries {
+                    name,
+                    values: vec![value],
+                }),
+            }
+        }
+    }
+
+    /// Take the pending frame once the frame interval has elapsed
+    pub(crate) fn take_frame(&mut self, port: &str) -> Option<PlotFrame> {
+        if self.last_frame.elapsed() < self.interval {
+            return None;
+        }
+        self.flush(port)
+    }
+
+    /// Take the pending frame now, regardless of the frame rate
+    pub(crate) fn flush(&mut self, port: &str) -> Option<PlotFrame> {
+        if self.samples == 0 {
+            return None;
+        }
+
+        self.last_frame = Instant::now();
+        let samples = std::mem::take(&mut self.samples);
+        let series = std::mem::take(&mut self.series)
+            .into_iter()
+            .map(|s| PlotSeries {
+                name: s.name,
+                values: decimate(&s.values, self.settings.max_points),
+            })
+            .collect();
+
+        Some(PlotFrame {
+            port: port.to_string(),
+            samples,
+            series,
+        })
+    }
+}
+
+/// Start plotting the output of an open monitor session
+#[tauri::command]
+pub async fn start_plotter(
+    sessions: State<'_, SerialSessions>,
+    port: String,
+    settings: Option<PlotterSettings>,
+) -> Result<(), SerialError> {
+    let taps = sessions.taps(&port)?;
+    taps.lock().unwrap().plotter = Some(Plotter::new(settings.unwrap_or_default()));
+    Ok(())
+}
+
+/// Stop plotting, the monitor session stays open
+#[tauri::command]
+pub async fn stop_plotter(
+    sessions: State<'_, SerialSessions>,
+    port: String,
+) -> Result<(), SerialError> {
+    let taps = sessions.taps(&port)?;
+    taps.lock().unwrap().plotter = None;
+    Ok(())
+}
+
+/// Parse a line into (series name, value) pairs
+/// Returns None if any field is not numeric, so text lines don't plot
+fn parse_line(line: &str) -> Option<Vec<(String, f64)>> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    // Commas/tabs take precedence so labels may contain spaces
+    let fields: Vec<&str> = if line.contains([',', '\t']) {
+        line.split([',', '\t']).map(str::trim).filter(|f| !f.is_empty()).collect()
+    } else {
+        line.split_whitespace().collect()
+    };
+
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match field.rsplit_once(':') {
+            Some((label, value)) => {
+                let label = label.trim();
+                let value = parse_number(value.trim())?;
+                (!label.is_empty()).then(|| (label.to_string(), value))
+            }
+            None => Some((format!("value {}", i + 1), parse_number(field)?)),
+        })
+        .collect()
+}
+
+/// Finite number, so words like "inf" or "nan" don't count as data
+fn parse_number(field: &str) -> Option<f64> {
+    field.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// Down-sample to at most `max_points` values, keeping each bucket's min and
+/// max in arrival order so spikes survive
+fn decimate(values: &[f64], max_points: usize) -> Vec<f64> {
+    if max_points < 2 || values.len() <= max_points {
+        return values.to_vec();
+    }
+
+    let buckets = max_points / 2;
+    let mut out = Vec::with_capacity(buckets * 2);
+    for b in 0..buckets {
+        let start = b * values.len() / buckets;
+        let end = (b + 1) * values.len() / buckets;
+        let bucket = &values[start..end];
+
+        let (mut min_i, mut max_i) = (0, 0);
+        for (i, v) in bucket.iter().enumerate() {
+            if *v < bucket[min_i] {
+                min_i = i;
+            }
+            if *v > bucket[max_i] {
+                max_i = i;
+            }
+        }
+
+        out.push(bucket[min_i.min(max_i)]);
+        out.push(bucket[min_i.max(max_i)]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_unlabeled_values() {
+        assert_eq!(
+            parse_line("512,3.3,-12"),
+            Some(vec![
+                ("value 1".to_string(), 512.0),
+                ("value 2".to_string(), 3.3),
+                ("value 3".to_string(), -12.0),
+            ])
+        );
+        assert_eq!(
+            parse_line("1023\t0"),
+            Some(vec![
+                ("value 1".to_string(), 1023.0),
+                ("value 2".to_string(), 0.0)
+            ])
+        );
+        assert_eq!(
+            parse_line("  7 8  "),
+            Some(vec![
+                ("value 1".to_string(), 7.0),
+                ("value 2".to_string(), 8.0)
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_labeled_values() {
+        // Serial.print("Temp:"); Serial.print(t); Serial.print(","); ...
+        assert_eq!(
+            parse_line("Temp:23.50,Humidity:41.00"),
+            Some(vec![
+                ("Temp".to_string(), 23.5),
+                ("Humidity".to_string(), 41.0)
+            ])
+        );
+        assert_eq!(
+            parse_line("Light level: 512\tTemp: 23.5"),
+            Some(vec![
+                ("Light level".to_string(), 512.0),
+                ("Temp".to_string(), 23.5)
+            ])
+        );
+        assert_eq!(
+            parse_line("x:1 y:-2"),
+            Some(vec![("x".to_string(), 1.0), ("y".to_string(), -2.0)])
+        );
+    }
+
+    #[test]
+    fn ignores_text_lines() {
+        assert_eq!(parse_line(""), None);
+        assert_eq!(parse_line("Starting up..."), None);
+        assert_eq!(parse_line("Temp: 23.5 C"), None);
+        assert_eq!(parse_line("12,abc"), None);
+        assert_eq!(parse_line(":12"), None);
+        assert_eq!(parse_line("nan"), None);
+        assert_eq!(parse_line("inf,1"), None);
+    }
+
+    #[test]
+    fn decimate_keeps_short_series() {
+        let values = [1.0, 2.0, 3.0];
+        assert_eq!(decimate(&values, 200), values.to_vec());
+        assert_eq!(decimate(&values, 1), values.to_vec());
+    }
+
+    #[test]
+    fn decimate_keeps_spikes_in_order() {
+        let mut values = vec![0.0; 1000];
+        values[123] = 100.0;
+        values[700] = -50.0;
+
+        let out = decimate(&values, 200);
+        assert_eq!(out.len(), 200);
+        assert!(out.contains(&100.0));
+        assert!(out.contains(&-50.0));
+        let spike = out.iter().position(|v| *v == 100.0).unwrap();
+        let dip = out.iter().position(|v| *v == -50.0).unwrap();
+        assert!(spike < dip);
+    }
+
+    #[test]
+    fn decimate_keeps_bucket_min_and_max() {
+        let values: Vec<f64> = (0..10).map(f64::from).collect();
+        assert_eq!(decimate(&values, 4), vec![0.0, 4.0, 5.0, 9.0]);
+    }
+
+    #[test]
+    fn frames_batch_numeric_lines() {
+        let mut plotter = Plotter::new(PlotterSettings::default());
+        plotter.push_line("a:1,b:2");
+        plotter.push_line("booting");
+        plotter.push_line("a:3,b:4");
+        plotter.push_line("c:5");
+
+        // Not due until a frame interval has passed
+        assert!(plotter.take_frame("/dev/ttyACM0").is_none());
+        plotter.last_frame -= plotter.interval;
+
+        let frame = plotter.take_frame("/dev/ttyACM0").unwrap();
+        assert_eq!(frame.port, "/dev/ttyACM0");
+        assert_eq!(frame.samples, 3);
+        let series: Vec<(&str, &[f64])> = frame
+            .series
+            .iter()
+            .map(|s| (s.name.as_str(), s.values.as_slice()))
+            .collect();
+        assert_eq!(
+            series,
+            vec![
+                ("a", &[1.0, 3.0][..]),
+                ("b", &[2.0, 4.0][..]),
+                ("c", &[5.0][..])
+            ]
+        );
+
+        plotter.last_frame -= plotter.interval;
+        assert!(plotter.take_frame("/dev/ttyACM0").is_none());
+    }
+}
@@ -0,0 +1,561 @@
+/*!
+ * Serial session recording
+ * Writes every line a monitor session receives to disk with host timestamps,
+ * and replays recordings through the same "serial-data" / "serial-plot" events
+ *
+ * Formats:
+ * - log:   `[2026-01-31T09:15:02.250Z] line`
+ * - csv:   `timestamp,elapsed_ms,line`
+ * - jsonl: `{"timestamp":"...","elapsed_ms":0,"line":"..."}`
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::plotter::{Plotter, PlotterSettings};
+use super::serial::{SerialClosed, SerialData, SerialError, SerialSessions};
+
+/// Replay gaps shorter than this are delivered in the same event
+const REPLAY_MIN_SLEEP_MS: u64 = 20;
+
+/// Largest batch of replayed text per event
+const REPLAY_MAX_BATCH: usize = 64 * 1024;
+
+/// Recording file format
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordFormat {
+    #[default]
+    Log,
+    Csv,
+    Jsonl,
+}
+
+impl RecordFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            RecordFormat::Log => "log",
+            RecordFormat::Csv => "csv",
+            RecordFormat::Jsonl => "jsonl",
+        }
+    }
+
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => RecordFormat::Csv,
+            Some("jsonl") | Some("json") => RecordFormat::Jsonl,
+            _ => RecordFormat::Log,
+        }
+    }
+}
+
+/// Result of a finished recording
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingSummary {
+    pub path: String,
+    pub lines: u64,
+    pub duration_ms: u64,
+}
+
+/// One JSON-lines record
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonRecord {
+    timestamp: String,
+    elapsed_ms: u64,
+    line: String,
+}
+
+/// Writes received lines to a recording file
+pub(crate) struct Recorder {
+    path: PathBuf,
+    format: RecordFormat,
+    writer: BufWriter<File>,
+    started: Instant,
+    lines: u64,
+    error: Option<String>,
+}
+
+impl Recorder {
+    fn create(path: PathBuf, format: RecordFormat) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(&path)?);
+        if let RecordFormat::Csv = format {
+            writeln!(writer, "timestamp,elapsed_ms,line")?;
+        }
+
+        Ok(Recorder {
+            path,
+            format,
+            writer,
+            started: Instant::now(),
+            lines: 0,
+            error: None,
+        })
+    }
+
+    /// Append a line, the first write error stops the recording
+    pub(crate) fn write_line(&mut self, line: &str) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let timestamp = format_utc(SystemTime::now());
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        let result = match self.format {
+            RecordFormat::Log => writeln!(self.writer, "[{}] {}", timestamp, line),
+            RecordFormat::Csv => writeln!(
+                self.writer,
+                "{},{},\"{}\"",
+                timestamp,
+                elapsed_ms,
+                line.replace('"', "\"\"")
+            ),
+            RecordFormat::Jsonl => {
+                let record = JsonRecord {
+                    timestamp,
+                    elapsed_ms,
+                    line: line.to_string(),
+                };
+                serde_json::to_string(&record)
+                    .map_err(std::io::Error::from)
+                    .and_then(|json| writeln!(self.writer, "{}", json))
+            }
+        };
+
+        match result {
+            Ok(()) => self.lines += 1,
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    fn finish(mut self) -> Result<RecordingSummary, SerialError> {
+        if let Err(e) = self.writer.flush() {
+            self.error.get_or_insert(e.to_string());
+        }
+        if let Some(error) = self.error {
+            return Err(SerialError::RecordingError(error));
+        }
+
+        Ok(RecordingSummary {
+            path: self.path.to_string_lossy().to_string(),
+            lines: self.lines,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+/// Running replays, keyed by their virtual port name
+#[derive(Default)]
+pub struct Replays {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// Start recording an open monitor session
+/// Writes to `path` if given, otherwise to the app data dir
+/// Returns the file path
+#[tauri::command]
+pub async fn start_recording(
+    app: AppHandle,
+    sessions: State<'_, SerialSessions>,
+    port: String,
+    format: Option<RecordFormat>,
+    path: Option<String>,
+) -> Result<String, SerialError> {
+    let taps = sessions.taps(&port)?;
+    let format = format.unwrap_or_default();
+
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => default_recording_path(&app, &port, format)?,
+    };
+
+    // Checked before creating the file, which would truncate the recording
+    // in progress if it has the same path
+    let mut taps = taps.lock().unwrap();
+    if taps.recorder.is_some() {
+        return Err(SerialError::RecordingError(format!(
+            "{} is already being recorded",
+            port
+        )));
+    }
+
+    let recorder = Recorder::create(path.clone(), format)
+        .map_err(|e| SerialError::RecordingError(e.to_string()))?;
+    taps.recorder = Some(recorder);
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Stop recording and close the file, the monitor session stays open
+#[tauri::command]
+pub async fn stop_recording(
+    sessions: State<'_, SerialSessions>,
+    port: String,
+) -> Result<RecordingSummary, SerialError> {
+    let recorder = sessions.taps(&port)?.lock().unwrap().recorder.take();
+
+    match recorder {
+        Some(recorder) => recorder.finish(),
+        None => Err(SerialError::RecordingError(format!(
+            "{} is not being recorded",
+            port
+        ))),
+    }
+}
+
+/// Replay a recording as "serial-data" events (and "serial-plot" frames if
+/// `plot` is set) on the virtual port "replay:<file name>", which is returned
+/// `speed` scales the recorded timing, 0 replays as fast as possible
+/// Ends with a "serial-closed" event like a real port
+#[tauri::command]
+pub async fn replay_recording(
+    app: AppHandle,
+    replays: State<'_, Replays>,
+    path: String,
+    speed: Option<f64>,
+    plot: Option<PlotterSettings>,
+) -> Result<String, SerialError> {
+    let path = PathBuf::from(path);
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| SerialError::RecordingError(e.to_string()))?;
+    let entries = parse_recording(&content, RecordFormat::from_path(&path));
+
+    let port = format!(
+        "replay:{}",
+        path.file_name().and_then(|n| n.to_str()).unwrap_or("recording")
+    );
+
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let mut map = replays.running.lock().unwrap();
+        if map.contains_key(&port) {
+            return Err(SerialError::AlreadyOpen(port));
+        }
+        map.insert(port.clone(), running.clone());
+    }
+
+    let replay_port = port.clone();
+    tauri::async_runtime::spawn(async move {
+        run_replay(&app, &replay_port, entries, speed.unwrap_or(1.0), plot, &running).await;
+        app.state::<Replays>().running.lock().unwrap().remove(&replay_port);
+        let _ = app.emit(
+            "serial-closed",
+            SerialClosed {
+                port: replay_port,
+                error: None,
+            },
+        );
+    });
+
+    Ok(port)
+}
+
+/// Stop a running replay
+#[tauri::command]
+pub async fn stop_replay(replays: State<'_, Replays>, port: String) -> Result<(), SerialError> {
+    match replays.running.lock().unwrap().get(&port) {
+        Some(running) => {
+            running.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+        None => Err(SerialError::NotOpen(port)),
+    }
+}
+
+/// Default file for a new recording: <app data>/recordings/<port>-<time>.<ext>
+fn default_recording_path(
+    app: &AppHandle,
+    port: &str,
+    format: RecordFormat,
+) -> Result<PathBuf, SerialError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| SerialError::RecordingError(e.to_string()))?
+        .join("recordings");
+    std::fs::create_dir_all(&dir).map_err(|e| SerialError::RecordingError(e.to_string()))?;
+
+    // "/dev/ttyUSB0" -> "ttyUSB0", "COM3" -> "COM3"
+    let port_name: String = port
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(port)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let stamp: String = format_utc(SystemTime::now())
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .take(14)
+        .collect();
+
+    Ok(dir.join(format!("{}-{}.{}", port_name, stamp, format.extension())))
+}
+
+/// Emit the recorded lines, batching those that arrive close together
+async fn run_replay(
+    app: &AppHandle,
+    port: &str,
+    entries: Vec<(u64, String)>,
+    speed: f64,
+    plot: Option<PlotterSettings>,
+    running: &AtomicBool,
+) {
+    let mut plotter = plot.map(Plotter::new);
+    let mut batch = String::new();
+    let started = Instant::now();
+
+    let flush = |batch: &mut String, plotter: &mut Option<Plotter>| {
+        if !batch.is_empty() {
+            let data = std::mem::take(batch);
+            let _ = app.emit(
+                "serial-data",
+                SerialData {
+                    port: port.to_string(),
+                    data,
+                },
+            );
+        }
+        if let Some(frame) = plotter.as_mut().and_then(|p| p.flush(port)) {
+            let _ = app.emit("serial-plot", frame);
+        }
+    };
+
+    for (elapsed_ms, line) in entries {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+
+        if speed > 0.0 {
+            let due = Duration::from_secs_f64(elapsed_ms as f64 / 1000.0 / speed);
+            let wait = due.saturating_sub(started.elapsed());
+            if wait >= Duration::from_millis(REPLAY_MIN_SLEEP_MS) {
+                flush(&mut batch, &mut plotter);
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        if let Some(plotter) = plotter.as_mut() {
+            plotter.push_line(&line);
+        }
+        batch.push_str(&line);
+        batch.push('\n');
+
+        if batch.len() >= REPLAY_MAX_BATCH {
+            flush(&mut batch, &mut plotter);
+            tokio::task::yield_now().await;
+        }
+    }
+
+    flush(&mut batch, &mut plotter);
+}
+
+/// Parse a recording into (elapsed ms, line) entries, skipping malformed lines
+fn parse_recording(content: &str, format: RecordFormat) -> Vec<(u64, String)> {
+    match format {
+        RecordFormat::Jsonl => content
+            .lines()
+            .filter_map(|l| serde_json::from_str::<JsonRecord>(l).ok())
+            .map(|r| (r.elapsed_ms, r.line))
+            .collect(),
+        RecordFormat::Csv => content
+            .lines()
+            .skip(1)
+            .filter_map(|l| {
+                let mut fields = l.splitn(3, ',');
+                let _timestamp = fields.next()?;
+                let elapsed_ms = fields.next()?.parse().ok()?;
+                let line = fields.next()?;
+                let line = line
+                    .strip_prefix('"')
+                    .and_then(|l| l.strip_suffix('"'))
+                    .unwrap_or(line)
+                    .replace("\"\"", "\"");
+                Some((elapsed_ms, line))
+            })
+            .collect(),
+        RecordFormat::Log => {
+            let mut first = None;
+            content
+                .lines()
+                .filter_map(|l| {
+                    let (timestamp, line) = l.strip_prefix('[')?.split_once("] ")?;
+                    let ms = parse_utc(timestamp)?;
+                    let start = *first.get_or_insert(ms);
+                    Some((ms.saturating_sub(start), line.to_string()))
+                })
+                .collect()
+        }
+    }
+}
+
+/// Format a time as RFC 3339 UTC with milliseconds
+fn format_utc(time: SystemTime) -> String {
+    let ms = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+    let (secs, millis) = (ms / 1000, ms % 1000);
+    let (days, rem) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        millis
+    )
+}
+
+/// Parse a timestamp written by format_utc back to ms since the epoch
+fn parse_utc(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let (hms, millis) = time.split_once('.').unwrap_or((time, "0"));
+    let mut hms = hms.splitn(3, ':').map(|p| p.parse::<u64>().ok());
+    let (h, m, s) = (hms.next()??, hms.next()??, hms.next()??);
+
+    // Days since 1970-01-01 from a civil date (inverse of format_utc)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u64::try_from(era * 146097 + doe - 719468).ok()?;
+
+    Some(((days * 86400 + h * 3600 + m * 60 + s) * 1000) + millis.parse::<u64>().ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn formats_utc_dates() {
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_utc(at(946684799999)), "1999-12-31T23:59:59.999Z");
+        // Leap days, 2000 is a leap year as a multiple of 400, 2100 is not
+        assert_eq!(format_utc(at(951868799999)), "2000-02-29T23:59:59.999Z");
+        assert_eq!(format_utc(at(1709210096789)), "2024-02-29T12:34:56.789Z");
+        assert_eq!(
+            format_utc(at(4107542400000 - 1)),
+            "2100-02-28T23:59:59.999Z"
+        );
+        assert_eq!(format_utc(at(4107542400000)), "2100-03-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn parses_utc_dates() {
+        assert_eq!(parse_utc("1970-01-01T00:00:00.000Z"), Some(0));
+        assert_eq!(parse_utc("2000-02-29T23:59:59.999Z"), Some(951868799999));
+        assert_eq!(parse_utc("2024-02-29T12:34:56.789Z"), Some(1709210096789));
+        assert_eq!(parse_utc("2100-03-01T00:00:00.000Z"), Some(4107542400000));
+        // Without milliseconds
+        assert_eq!(parse_utc("2024-02-29T12:34:56Z"), Some(1709210096000));
+    }
+
+    #[test]
+    fn rejects_bad_timestamps() {
+        assert_eq!(parse_utc(""), None);
+        assert_eq!(parse_utc("2024-02-29T12:34:56.789"), None);
+        assert_eq!(parse_utc("2024-02-29 12:34:56.789Z"), None);
+        assert_eq!(parse_utc("2024-02T12:34:56.789Z"), None);
+        // Before the epoch
+        assert_eq!(parse_utc("1969-12-31T23:59:59.999Z"), None);
+    }
+
+    #[test]
+    fn utc_round_trips() {
+        for ms in [
+            0,
+            59_999,
+            86_400_000,
+            951_782_400_000,
+            1_709_210_096_789,
+            4_107_542_399_999,
+        ] {
+            assert_eq!(parse_utc(&format_utc(at(ms))), Some(ms));
+        }
+    }
+
+    #[test]
+    fn parses_log_recordings() {
+        let content = "[2024-02-29T23:59:59.500Z] Temp:23.5\n\
+                       garbage\n\
+                       [2024-03-01T00:00:00.750Z] Temp:23.6\n";
+        assert_eq!(
+            parse_recording(content, RecordFormat::Log),
+            vec![
+                (0, "Temp:23.5".to_string()),
+                (1250, "Temp:23.6".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_csv_recordings() {
+        let content = "timestamp,elapsed_ms,line\n\
+                       2024-02-29T12:00:00.000Z,0,\"a,b\"\n\
+                       2024-02-29T12:00:00.020Z,20,\"say \"\"hi\"\"\"\n\
+                       2024-02-29T12:00:00.040Z,x,\"bad\"\n";
+        assert_eq!(
+            parse_recording(content, RecordFormat::Csv),
+            vec![(0, "a,b".to_string()), (20, "say \"hi\"".to_string())]
+        );
+    }
+
+    #[test]
+    fn parses_jsonl_recordings() {
+        let content = "{\"timestamp\":\"2024-02-29T12:00:00.000Z\",\"elapsed_ms\":0,\"line\":\"ready\"}\n\
+                       not json\n\
+                       {\"timestamp\":\"2024-02-29T12:00:00.100Z\",\"elapsed_ms\":100,\"line\":\"1,2\"}\n";
+        assert_eq!(
+            parse_recording(content, RecordFormat::Jsonl),
+            vec![(0, "ready".to_string()), (100, "1,2".to_string())]
+        );
+    }
+
+    #[test]
+    fn recorded_csv_replays_the_same_lines() {
+        let path =
+            std::env::temp_dir().join(format!("hduino-recording-{}.csv", std::process::id()));
+        let mut recorder = Recorder::create(path.clone(), RecordFormat::Csv).unwrap();
+        for line in ["plain", "a,b", "quote \" inside"] {
+            recorder.write_line(line);
+        }
+        let summary = recorder.finish().unwrap();
+        assert_eq!(summary.lines, 3);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<String> = parse_recording(&content, RecordFormat::Csv)
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect();
+        assert_eq!(lines, ["plain", "a,b", "quote \" inside"]);
+    }
+}
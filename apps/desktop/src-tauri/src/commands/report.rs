@@ -0,0 +1,177 @@
+/*!
+ * Compile report
+ * Flash and RAM usage parsed from arduino-cli's size summary:
+ * - "Sketch uses 924 bytes (2%) of program storage space. Maximum is 32256 bytes."
+ * - "Global variables use 9 bytes (0%) of dynamic memory, leaving 2039 bytes
+ *   for local variables. Maximum is 2048 bytes."
+ */
+
+use serde::{Deserialize, Serialize};
+
+/// Usage above this percentage is flagged unless the caller picks another
+pub(crate) const DEFAULT_WARNING_PERCENT: u8 = 90;
+
+/// Memory usage of one region, `max` is None when the core doesn't report it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    pub used: u64,
+    pub max: Option<u64>,
+    pub percent: Option<u8>,
+    /// Usage is at or above the warning threshold
+    pub warning: bool,
+}
+
+/// Result of a successful compile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompileReport {
+    /// Pass to upload_build to upload this build without recompiling
+    pub build_id: String,
+    pub build_path: String,
+    /// Program storage space
+    pub flash: Option<MemoryUsage>,
+    /// Dynamic memory used by global variables
+    pub ram: Option<MemoryUsage>,
+    pub warning_percent: u8,
+}
+
+impl CompileReport {
+    /// Build a report from the compile output lines
+    pub(crate) fn parse(
+        build_id: String,
+        build_path: String,
+        output: &[String],
+        warning_percent: u8,
+    ) -> Self {
+        let mut report = CompileReport {
+            build_id,
+            build_path,
+            flash: None,
+            ram: None,
+            warning_percent,
+        };
+
+        for line in output {
+            if line.starts_with("Sketch uses") {
+                report.flash = parse_usage(line, warning_percent);
+            } else if line.starts_with("Global variables use") {
+                report.ram = parse_usage(line, warning_percent);
+            }
+        }
+
+        report
+    }
+}
+
+/// First number is the usage, the one after "Maximum is" the size
+fn parse_usage(line: &str, warning_percent: u8) -> Option<MemoryUsage> {
+    let used = numbers(line).next()?;
+    let max = line
+        .split_once("Maximum is")
+        .and_then(|(_, rest)| numbers(rest).next())
+        .filter(|max| *max > 0);
+
+    let percent = max.map(|max| (used * 100 / max).min(255) as u8);
+
+    Some(MemoryUsage {
+        used,
+        max,
+        percent,
+        warning: percent.is_some_and(|p| p >= warning_percent),
+    })
+}
+
+/// Numbers in a line, thousands separators ("262,245") included
+fn numbers(text: &str) -> impl Iterator<Item = u64> + '_ {
+    text.split(|c: char| !c.is_ascii_digit() && c != ',')
+        .map(|word| word.trim_matches(',').replace(',', ""))
+        .filter_map(|word| word.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(output: &[&str]) -> CompileReport {
+        let output: Vec<String> = output.iter().map(|line| line.to_string()).collect();
+        CompileReport::parse(
+            "build".to_string(),
+            "/tmp/build".to_string(),
+            &output,
+            DEFAULT_WARNING_PERCENT,
+        )
+    }
+
+    #[test]
+    fn parses_avr_summary() {
+        let report = report(&[
+            "Sketch uses 924 bytes (2%) of program storage space. Maximum is 32256 bytes.",
+            "Global variables use 9 bytes (0%) of dynamic memory, leaving 2039 bytes for local variables. Maximum is 2048 bytes.",
+        ]);
+
+        let flash = report.flash.unwrap();
+        assert_eq!(
+            (flash.used, flash.max, flash.percent),
+            (924, Some(32256), Some(2))
+        );
+        assert!(!flash.warning);
+
+        let ram = report.ram.unwrap();
+        assert_eq!((ram.used, ram.max, ram.percent), (9, Some(2048), Some(0)));
+    }
+
+    #[test]
+    fn parses_esp32_summary() {
+        let report = report(&[
+            "Sketch uses 262245 bytes (20%) of program storage space. Maximum is 1310720 bytes.",
+            "Global variables use 20552 bytes (6%) of dynamic memory, leaving 307128 bytes for local variables. Maximum is 327680 bytes.",
+        ]);
+
+        assert_eq!(report.flash.unwrap().used, 262245);
+        assert_eq!(report.ram.unwrap().max, Some(327680));
+    }
+
+    #[test]
+    fn parses_thousands_separators() {
+        let report = report(&[
+            "Sketch uses 262,245 bytes (20%) of program storage space. Maximum is 1,310,720 bytes.",
+        ]);
+
+        let flash = report.flash.unwrap();
+        assert_eq!((flash.used, flash.max), (262245, Some(1310720)));
+    }
+
+    #[test]
+    fn flags_usage_above_threshold() {
+        let report = report(&[
+            "Sketch uses 35012 bytes (108%) of program storage space. Maximum is 32256 bytes.",
+            "Global variables use 1900 bytes (92%) of dynamic memory, leaving 148 bytes for local variables. Maximum is 2048 bytes.",
+            "Low memory available, stability problems may occur.",
+        ]);
+
+        let flash = report.flash.unwrap();
+        assert_eq!(flash.percent, Some(108));
+        assert!(flash.warning);
+        assert!(report.ram.unwrap().warning);
+    }
+
+    #[test]
+    fn usage_without_maximum() {
+        let report = report(&["Sketch uses 10680 bytes of program storage space."]);
+
+        let flash = report.flash.unwrap();
+        assert_eq!((flash.used, flash.max, flash.percent), (10680, None, None));
+        assert!(!flash.warning);
+        assert!(report.ram.is_none());
+    }
+
+    #[test]
+    fn ignores_other_output() {
+        let report = report(&[
+            "Used library Servo Version 1.2.1 /home/user/Arduino/libraries/Servo",
+            "Used platform Version Path",
+        ]);
+
+        assert!(report.flash.is_none());
+        assert!(report.ram.is_none());
+    }
+}
@@ -1,44 +1,954 @@
 use serde::{Deserialize, Serialize};
-use serialport::available_ports;
+use serialport::{available_ports, SerialPort};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
 
-#[derive(Debug, Serialize, Deserialize)]
+use super::boards;
+use super::plotter::Plotter;
+use super::recording::Recorder;
+
+/// Read timeout for the background reader, also bounds how long close() waits
+const READ_TIMEOUT_MS: u64 = 50;
+
+/// Longest line kept while waiting for a newline, longer output is split
+const MAX_LINE_LEN: usize = 4096;
+
+/// Poll interval for the hot-plug port watcher
+const PORT_WATCH_INTERVAL_MS: u64 = 1000;
+
+/// How long to wait for a port to come back after an upload (attempts x interval)
+const RESUME_ATTEMPTS: u32 = 20;
+const RESUME_RETRY_MS: u64 = 250;
+
+/// Rates tried by detect_baud_rate, same list as SERIAL_SPEEDS in boards.ts
+const BAUD_RATES: &[u32] = &[
+    300, 600, 1200, 2400, 4800, 9600, 14400, 19200, 28800, 31250, 38400, 57600, 115200,
+];
+
+/// Time given to a board to boot after the port open resets it
+const BAUD_SETTLE_MS: u64 = 1500;
+
+/// Default sample window per rate for detect_baud_rate
+const BAUD_SAMPLE_MS: u64 = 300;
+
+/// Bytes needed in a window before its score counts
+const BAUD_MIN_BYTES: usize = 8;
+
+/// Best score needed to report a rate
+const BAUD_MIN_SCORE: f64 = 0.9;
+
+/// How long to wait for the bootloader port after a 1200-baud touch
+const BOOTLOADER_WAIT_MS: u64 = 10000;
+const BOOTLOADER_POLL_MS: u64 = 250;
+
+/// How a port is connected to the host
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortKind {
+    Usb,
+    Pci,
+    Bluetooth,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SerialPortInfo {
     pub path: String,
+    pub port_type: PortKind,
     pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
     pub vendor_id: Option<String>,
     pub product_id: Option<String>,
+    /// USB interface number, tells apart the ports of multi-port adapters
+    pub interface: Option<u8>,
+}
+
+/// Parity setting for a monitor session
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Parity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+/// Line ending appended to every write
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    None,
+    #[default]
+    Nl,
+    Cr,
+    CrLf,
+}
+
+impl LineEnding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::None => "",
+            LineEnding::Nl => "\n",
+            LineEnding::Cr => "\r",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How to reset a board through the USB-serial control lines
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetMethod {
+    /// Pulse DTR/RTS low then high, the Uno/Nano/Mega auto-reset circuit
+    #[default]
+    Dtr,
+    /// Pull EN low through RTS, the ESP32/ESP8266 auto-reset circuit
+    Esp,
+}
+
+/// Serial monitor settings, mirrors the Arduino IDE monitor options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorSettings {
+    pub baud_rate: u32,
+    #[serde(default = "default_data_bits")]
+    pub data_bits: u8,
+    #[serde(default)]
+    pub parity: Parity,
+    #[serde(default = "default_stop_bits")]
+    pub stop_bits: u8,
+    #[serde(default)]
+    pub line_ending: LineEnding,
+}
+
+fn default_data_bits() -> u8 {
+    8
+}
+
+fn default_stop_bits() -> u8 {
+    1
+}
+
+/// Data received from an open port ("serial-data" event)
+#[derive(Debug, Clone, Serialize)]
+pub struct SerialData {
+    pub port: String,
+    pub data: String,
+}
+
+/// Monitor session closed ("serial-closed" event)
+/// `error` is set when the port went away, e.g. board unplugged
+#[derive(Debug, Clone, Serialize)]
+pub struct SerialClosed {
+    pub port: String,
+    pub error: Option<String>,
+}
+
+/// Monitor session paused while an upload uses the port ("serial-paused" event)
+#[derive(Debug, Clone, Serialize)]
+pub struct SerialPaused {
+    pub port: String,
+}
+
+/// Monitor session reopened after an upload ("serial-resumed" event)
+/// `port` differs from `previous_port` when the board re-enumerated
+#[derive(Debug, Clone, Serialize)]
+pub struct SerialResumed {
+    pub port: String,
+    pub previous_port: String,
 }
 
 #[derive(Debug, Serialize, thiserror::Error)]
 pub enum SerialError {
     #[error("Failed to list ports: {0}")]
     ListError(String),
+    #[error("Failed to open port: {0}")]
+    OpenError(String),
+    #[error("Port already open: {0}")]
+    AlreadyOpen(String),
+    #[error("Port not open: {0}")]
+    NotOpen(String),
+    #[error("Port busy: {0} is being used by an upload")]
+    PortBusy(String),
+    #[error("Failed to write to port: {0}")]
+    WriteError(String),
+    #[error("Invalid serial settings: {0}")]
+    InvalidSettings(String),
+    #[error("Failed to set control lines: {0}")]
+    ControlError(String),
+    #[error("Bootloader port did not appear after touching {0}")]
+    BootloaderTimeout(String),
+    #[error("Recording error: {0}")]
+    RecordingError(String),
+}
+
+/// How readable the data sampled at one rate was
+#[derive(Debug, Clone, Serialize)]
+pub struct BaudScore {
+    pub baud_rate: u32,
+    pub bytes: usize,
+    /// 0-1, share of printable bytes (lower without any line break)
+    pub score: f64,
+}
+
+/// Result of detect_baud_rate, `baud_rate` is None if nothing looked like text
+#[derive(Debug, Clone, Serialize)]
+pub struct BaudDetection {
+    pub baud_rate: Option<u32>,
+    pub scores: Vec<BaudScore>,
+}
+
+/// Consumers of the lines a session receives, shared with its reader thread
+#[derive(Default)]
+pub(crate) struct SessionTaps {
+    pub(crate) plotter: Option<Plotter>,
+    pub(crate) recorder: Option<Recorder>,
+}
+
+/// An open monitor session: the write handle plus its background reader
+struct SerialSession {
+    id: u64,
+    writer: Box<dyn SerialPort>,
+    settings: MonitorSettings,
+    taps: Arc<Mutex<SessionTaps>>,
+    running: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl SerialSession {
+    /// Stop the reader thread and release the port
+    fn stop(mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.reader.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Registry of open monitor sessions, keyed by port path
+/// Also arbitrates port ownership between the monitor and uploads
+#[derive(Default)]
+pub struct SerialSessions {
+    sessions: Mutex<HashMap<String, SerialSession>>,
+    /// Ports claimed by an upload, open_serial is refused until released
+    claimed: Mutex<HashSet<String>>,
+    next_id: AtomicU64,
+}
+
+/// Exclusive claim on a port, held for the duration of an upload
+pub(crate) struct PortClaim {
+    port: String,
+    /// Monitor session paused by the claim, reopened on release
+    paused: Option<(MonitorSettings, Arc<Mutex<SessionTaps>>)>,
+    /// USB identity of the port, used to find it again if it re-enumerates
+    device: Option<SerialPortInfo>,
+}
+
+impl SerialSessions {
+    /// Open a monitor session and start its background reader
+    fn open(
+        &self,
+        app: AppHandle,
+        port: String,
+        settings: MonitorSettings,
+        taps: Arc<Mutex<SessionTaps>>,
+    ) -> Result<(), SerialError> {
+        let mut map = self.sessions.lock().unwrap();
+        if map.contains_key(&port) {
+            return Err(SerialError::AlreadyOpen(port));
+        }
+        if self.claimed.lock().unwrap().contains(&port) {
+            return Err(SerialError::PortBusy(port));
+        }
+
+        let writer = open_port(&port, &settings)?;
+        let reader_port = writer
+            .try_clone()
+            .map_err(|e| SerialError::OpenError(e.to_string()))?;
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let running = Arc::new(AtomicBool::new(true));
+        let reader = spawn_reader(
+            app,
+            port.clone(),
+            id,
+            reader_port,
+            taps.clone(),
+            running.clone(),
+        );
+
+        map.insert(
+            port,
+            SerialSession {
+                id,
+                writer,
+                settings,
+                taps,
+                running,
+                reader: Some(reader),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Line consumers of an open session
+    pub(crate) fn taps(&self, port: &str) -> Result<Arc<Mutex<SessionTaps>>, SerialError> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(port)
+            .map(|s| s.taps.clone())
+            .ok_or_else(|| SerialError::NotOpen(port.to_string()))
+    }
+
+    /// Claim a port for exclusive use, pausing any monitor session on it
+    pub(crate) fn claim(&self, app: &AppHandle, port: &str) -> Result<PortClaim, SerialError> {
+        if !self.claimed.lock().unwrap().insert(port.to_string()) {
+            return Err(SerialError::PortBusy(port.to_string()));
+        }
+
+        let device = scan_ports(true)
+            .ok()
+            .and_then(|ports| ports.into_iter().find(|p| p.path == port));
+
+        // Release the registry lock before joining the reader
+        let session = self.sessions.lock().unwrap().remove(port);
+        let paused = session.map(|session| {
+            let paused = (session.settings.clone(), session.taps.clone());
+            session.stop();
+            let _ = app.emit("serial-paused", SerialPaused { port: port.to_string() });
+            paused
+        });
+
+        Ok(PortClaim {
+            port: port.to_string(),
+            paused,
+            device,
+        })
+    }
+
+    /// Release a claim and reopen the monitor session it paused
+    /// Waits for the port to come back, following it if the board re-enumerated
+    pub(crate) async fn release(&self, app: &AppHandle, claim: PortClaim) {
+        self.claimed.lock().unwrap().remove(&claim.port);
+
+        let Some((settings, taps)) = claim.paused else {
+            return;
+        };
+
+        for _ in 0..RESUME_ATTEMPTS {
+            if let Some(path) = find_device(&claim.port, claim.device.as_ref()) {
+                match self.open(app.clone(), path.clone(), settings.clone(), taps.clone()) {
+                    Ok(()) => {
+                        let _ = app.emit(
+                            "serial-resumed",
+                            SerialResumed {
+                                port: path,
+                                previous_port: claim.port,
+                            },
+                        );
+                        return;
+                    }
+                    // Reopened by the user in the meantime
+                    Err(SerialError::AlreadyOpen(_)) => return,
+                    Err(_) => {}
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(RESUME_RETRY_MS)).await;
+        }
+
+        let _ = app.emit(
+            "serial-closed",
+            SerialClosed {
+                port: claim.port,
+                error: Some("Port did not come back after upload".to_string()),
+            },
+        );
+    }
+}
+
+/// List serial ports
+/// Only USB ports (real hardware) unless `include_all` is set, which adds
+/// Bluetooth, PCI and virtual ports
+#[tauri::command]
+pub fn list_ports(include_all: Option<bool>) -> Result<Vec<SerialPortInfo>, SerialError> {
+    scan_ports(include_all.unwrap_or(false))
+}
+
+/// Open a port and start streaming its output as "serial-data" events
+#[tauri::command]
+pub async fn open_serial(
+    app: AppHandle,
+    sessions: State<'_, SerialSessions>,
+    port: String,
+    settings: MonitorSettings,
+) -> Result<(), SerialError> {
+    sessions.open(app, port, settings, Default::default())
+}
+
+/// Close a port opened with open_serial
+#[tauri::command]
+pub async fn close_serial(
+    app: AppHandle,
+    sessions: State<'_, SerialSessions>,
+    port: String,
+) -> Result<(), SerialError> {
+    // Release the registry lock before joining, the reader may need it to exit
+    let session = sessions.sessions.lock().unwrap().remove(&port);
+
+    match session {
+        Some(session) => {
+            session.stop();
+            let _ = app.emit("serial-closed", SerialClosed { port, error: None });
+            Ok(())
+        }
+        None => Err(SerialError::NotOpen(port)),
+    }
+}
+
+/// Send text to an open port, followed by the session's line ending
+#[tauri::command]
+pub async fn write_serial(
+    sessions: State<'_, SerialSessions>,
+    port: String,
+    data: String,
+) -> Result<(), SerialError> {
+    let mut map = sessions.sessions.lock().unwrap();
+    let session = map
+        .get_mut(&port)
+        .ok_or_else(|| SerialError::NotOpen(port.clone()))?;
+
+    let payload = format!("{}{}", data, session.settings.line_ending.as_str());
+    session
+        .writer
+        .write_all(payload.as_bytes())
+        .and_then(|_| session.writer.flush())
+        .map_err(|e| SerialError::WriteError(e.to_string()))
+}
+
+/// Set the DTR and/or RTS lines of an open port, unset lines are left as is
+#[tauri::command]
+pub async fn set_serial_signals(
+    sessions: State<'_, SerialSessions>,
+    port: String,
+    dtr: Option<bool>,
+    rts: Option<bool>,
+) -> Result<(), SerialError> {
+    let mut map = sessions.sessions.lock().unwrap();
+    let session = map
+        .get_mut(&port)
+        .ok_or_else(|| SerialError::NotOpen(port.clone()))?;
+
+    if let Some(level) = dtr {
+        session
+            .writer
+            .write_data_terminal_ready(level)
+            .map_err(|e| SerialError::ControlError(e.to_string()))?;
+    }
+    if let Some(level) = rts {
+        session
+            .writer
+            .write_request_to_send(level)
+            .map_err(|e| SerialError::ControlError(e.to_string()))?;
+    }
+
+    Ok(())
 }
 
+/// Reset the board on a port by toggling DTR/RTS
+/// Uses the open monitor session if there is one, so its output keeps streaming
 #[tauri::command]
-pub fn list_ports() -> Result<Vec<SerialPortInfo>, SerialError> {
+pub async fn reset_board(
+    sessions: State<'_, SerialSessions>,
+    port: String,
+    method: Option<ResetMethod>,
+) -> Result<(), SerialError> {
+    let shared = match sessions.sessions.lock().unwrap().get(&port) {
+        Some(session) => Some(
+            session
+                .writer
+                .try_clone()
+                .map_err(|e| SerialError::OpenError(e.to_string()))?,
+        ),
+        None => None,
+    };
+
+    let mut serial = match shared {
+        Some(serial) => serial,
+        None => {
+            if sessions.claimed.lock().unwrap().contains(&port) {
+                return Err(SerialError::PortBusy(port));
+            }
+            serialport::new(&port, 115200)
+                .open()
+                .map_err(|e| SerialError::OpenError(e.to_string()))?
+        }
+    };
+
+    pulse_reset(serial.as_mut(), method.unwrap_or_default()).await
+}
+
+/// Open the port at 1200 baud and drop DTR, the reset-to-bootloader signal of
+/// native USB boards (Leonardo, Micro, Zero...)
+/// Returns the bootloader port, which often has a different path; boards
+/// whose upload doesn't use the touch (`board` is the FQBN) are left alone
+/// and their port is returned as is
+/// A monitor open on the port is closed, not paused: the frontend reopens it
+/// once the upload to the bootloader is done
+#[tauri::command]
+pub async fn touch_1200bps(
+    app: AppHandle,
+    sessions: State<'_, SerialSessions>,
+    port: String,
+    board: String,
+) -> Result<String, SerialError> {
+    let touch = boards::board_property(&app, &board, "upload.use_1200bps_touch");
+    if touch.as_deref() != Some("true") {
+        return Ok(port);
+    }
+
+    let mut claim = sessions.claim(&app, &port)?;
+    let result = touch_and_wait(&port).await;
+
+    // Reopening the monitor would grab the bootloader when it keeps the
+    // sketch's path, and block the upload that follows
+    if claim.paused.take().is_some() {
+        let _ = app.emit(
+            "serial-closed",
+            SerialClosed {
+                port: port.clone(),
+                error: Some("Board reset into its bootloader".to_string()),
+            },
+        );
+    }
+    sessions.release(&app, claim).await;
+
+    result
+}
+
+/// Guess the baud rate a board is printing at
+/// Samples the port at every common rate and scores how printable the data is,
+/// needs a sketch that keeps printing (the port open resets the board once)
+#[tauri::command]
+pub async fn detect_baud_rate(
+    app: AppHandle,
+    sessions: State<'_, SerialSessions>,
+    port: String,
+    sample_ms: Option<u64>,
+) -> Result<BaudDetection, SerialError> {
+    let claim = sessions.claim(&app, &port)?;
+    let sample_port = port.clone();
+    let sample_ms = sample_ms.unwrap_or(BAUD_SAMPLE_MS);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        sample_baud_rates(&sample_port, sample_ms)
+    })
+    .await
+    .unwrap_or_else(|e| Err(SerialError::OpenError(e.to_string())));
+    sessions.release(&app, claim).await;
+
+    let scores = result?;
+    let baud_rate = scores
+        .iter()
+        .filter(|s| s.score >= BAUD_MIN_SCORE)
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+        .map(|s| s.baud_rate);
+
+    Ok(BaudDetection { baud_rate, scores })
+}
+
+/// Baud rate from the sketch's `Serial.begin(N)`, so the monitor can open at
+/// the rate the uploaded code uses
+#[tauri::command]
+pub fn sketch_baud_rate(code: String) -> Option<u32> {
+    code.lines()
+        .map(|line| line.split("//").next().unwrap_or(""))
+        .find_map(|line| {
+            line.match_indices("Serial.begin").find_map(|(i, _)| {
+                // Skip mySerial.begin / SoftwareSerial instances
+                let prev = line[..i].chars().next_back();
+                if matches!(prev, Some(c) if c.is_alphanumeric() || c == '_') {
+                    return None;
+                }
+
+                let args = line[i + "Serial.begin".len()..].trim_start().strip_prefix('(')?;
+                let digits: String = args
+                    .trim_start()
+                    .chars()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect();
+                digits.parse().ok()
+            })
+        })
+}
+
+/// Start the background hot-plug watcher
+/// Diffs the port list every second and emits "port-added" / "port-removed"
+/// with the full SerialPortInfo of the port that changed (all port types)
+pub fn start_port_watcher(app: AppHandle) {
+    thread::spawn(move || {
+        let mut known = scan_ports(true).unwrap_or_default();
+
+        loop {
+            thread::sleep(Duration::from_millis(PORT_WATCH_INTERVAL_MS));
+
+            let current = match scan_ports(true) {
+                Ok(ports) => ports,
+                Err(_) => continue,
+            };
+
+            // A different device on the same path counts as removed + added
+            for port in known.iter().filter(|p| !current.contains(p)) {
+                let _ = app.emit("port-removed", port.clone());
+            }
+            for port in current.iter().filter(|p| !known.contains(p)) {
+                let _ = app.emit("port-added", port.clone());
+            }
+
+            known = current;
+        }
+    });
+}
+
+/// Enumerate serial ports, USB only unless `include_all` is set
+fn scan_ports(include_all: bool) -> Result<Vec<SerialPortInfo>, SerialError> {
     let ports = available_ports().map_err(|e| SerialError::ListError(e.to_string()))?;
 
     Ok(ports
         .into_iter()
-        // Only include USB ports (real hardware), filter out legacy /dev/ttyS* ports
-        .filter(|port| matches!(port.port_type, serialport::SerialPortType::UsbPort(_)))
-        .map(|port| {
-            let (manufacturer, vendor_id, product_id) = match port.port_type {
-                serialport::SerialPortType::UsbPort(info) => (
-                    info.manufacturer,
-                    Some(format!("{:04X}", info.vid)),
-                    Some(format!("{:04X}", info.pid)),
-                ),
-                _ => (None, None, None),
-            };
-
-            SerialPortInfo {
+        // Legacy /dev/ttyS* ports show up as PCI/unknown, hide them by default
+        .filter(|port| {
+            include_all || matches!(port.port_type, serialport::SerialPortType::UsbPort(_))
+        })
+        .map(|port| match port.port_type {
+            serialport::SerialPortType::UsbPort(info) => SerialPortInfo {
                 path: port.port_name,
-                manufacturer,
-                vendor_id,
-                product_id,
-            }
+                port_type: PortKind::Usb,
+                manufacturer: info.manufacturer,
+                product: info.product,
+                serial_number: info.serial_number,
+                vendor_id: Some(format!("{:04X}", info.vid)),
+                product_id: Some(format!("{:04X}", info.pid)),
+                interface: info.interface,
+            },
+            other => SerialPortInfo {
+                path: port.port_name,
+                port_type: match other {
+                    serialport::SerialPortType::PciPort => PortKind::Pci,
+                    serialport::SerialPortType::BluetoothPort => PortKind::Bluetooth,
+                    _ => PortKind::Unknown,
+                },
+                manufacturer: None,
+                product: None,
+                serial_number: None,
+                vendor_id: None,
+                product_id: None,
+                interface: None,
+            },
         })
         .collect())
 }
+
+/// Toggle the control lines to reset a board
+async fn pulse_reset(serial: &mut dyn SerialPort, method: ResetMethod) -> Result<(), SerialError> {
+    let control = |e: serialport::Error| SerialError::ControlError(e.to_string());
+
+    match method {
+        ResetMethod::Dtr => {
+            serial.write_data_terminal_ready(false).map_err(control)?;
+            serial.write_request_to_send(false).map_err(control)?;
+            tokio::time::sleep(Duration::from_millis(250)).await;
+            serial.write_data_terminal_ready(true).map_err(control)?;
+            serial.write_request_to_send(true).map_err(control)?;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        ResetMethod::Esp => {
+            serial.write_data_terminal_ready(false).map_err(control)?;
+            serial.write_request_to_send(true).map_err(control)?;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            serial.write_request_to_send(false).map_err(control)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the port at every rate in BAUD_RATES and score each sample
+/// The port stays open and only the rate changes, so the board resets once
+/// (DTR stays high at 1200 baud, so Leonardo-class boards don't reset)
+fn sample_baud_rates(port: &str, sample_ms: u64) -> Result<Vec<BaudScore>, SerialError> {
+    let mut serial = serialport::new(port, BAUD_RATES[0])
+        .timeout(Duration::from_millis(READ_TIMEOUT_MS))
+        .open()
+        .map_err(|e| SerialError::OpenError(e.to_string()))?;
+    thread::sleep(Duration::from_millis(BAUD_SETTLE_MS));
+
+    let mut scores = Vec::new();
+    for &baud_rate in BAUD_RATES {
+        serial
+            .set_baud_rate(baud_rate)
+            .map_err(|e| SerialError::InvalidSettings(e.to_string()))?;
+        let _ = serial.clear(serialport::ClearBuffer::Input);
+
+        let sample = read_for(serial.as_mut(), Duration::from_millis(sample_ms))?;
+        scores.push(BaudScore {
+            baud_rate,
+            bytes: sample.len(),
+            score: printable_score(&sample),
+        });
+    }
+
+    Ok(scores)
+}
+
+/// Read whatever arrives within `window`
+fn read_for(serial: &mut dyn SerialPort, window: Duration) -> Result<Vec<u8>, SerialError> {
+    let started = std::time::Instant::now();
+    let mut sample = Vec::new();
+    let mut buf = [0u8; 256];
+
+    while started.elapsed() < window {
+        match serial.read(&mut buf) {
+            Ok(n) => sample.extend_from_slice(&buf[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(SerialError::OpenError(e.to_string())),
+        }
+    }
+
+    Ok(sample)
+}
+
+/// Share of printable ASCII / whitespace bytes, reduced when no line break
+/// was seen; too small a sample scores 0
+fn printable_score(sample: &[u8]) -> f64 {
+    if sample.len() < BAUD_MIN_BYTES {
+        return 0.0;
+    }
+
+    let printable = sample
+        .iter()
+        .filter(|&&b| (0x20..0x7F).contains(&b) || matches!(b, b'\r' | b'\n' | b'\t'))
+        .count();
+    let ratio = printable as f64 / sample.len() as f64;
+
+    if sample.contains(&b'\n') {
+        ratio
+    } else {
+        ratio * 0.9
+    }
+}
+
+/// 1200-baud touch, then wait for the bootloader port to enumerate
+async fn touch_and_wait(port: &str) -> Result<String, SerialError> {
+    let before: HashSet<String> = scan_ports(true)?.into_iter().map(|p| p.path).collect();
+
+    {
+        let mut serial = serialport::new(port, 1200)
+            .open()
+            .map_err(|e| SerialError::OpenError(e.to_string()))?;
+        serial
+            .write_data_terminal_ready(false)
+            .map_err(|e| SerialError::ControlError(e.to_string()))?;
+    }
+
+    let mut original_gone = false;
+    for _ in 0..(BOOTLOADER_WAIT_MS / BOOTLOADER_POLL_MS) {
+        tokio::time::sleep(Duration::from_millis(BOOTLOADER_POLL_MS)).await;
+
+        let Ok(ports) = scan_ports(true) else {
+            continue;
+        };
+
+        // A new path is the bootloader
+        if let Some(new_port) = ports.iter().find(|p| !before.contains(&p.path)) {
+            return Ok(new_port.path.clone());
+        }
+
+        // Same path reused: the port must go away and come back
+        let present = ports.iter().any(|p| p.path == port);
+        if !present {
+            original_gone = true;
+        } else if original_gone {
+            return Ok(port.to_string());
+        }
+    }
+
+    // Boards without native USB never re-enumerate, the port stays as is
+    if !original_gone {
+        return Ok(port.to_string());
+    }
+
+    Err(SerialError::BootloaderTimeout(port.to_string()))
+}
+
+/// Current path of a claimed port: the original path if it is back,
+/// otherwise the same USB device (serial number, then VID/PID) at a new path
+fn find_device(path: &str, device: Option<&SerialPortInfo>) -> Option<String> {
+    let ports = scan_ports(true).ok()?;
+    if ports.iter().any(|p| p.path == path) {
+        return Some(path.to_string());
+    }
+
+    let device = device?;
+    if device.serial_number.is_some() {
+        return ports
+            .into_iter()
+            .find(|p| p.serial_number == device.serial_number)
+            .map(|p| p.path);
+    }
+
+    // Without a serial number only a unique VID/PID match is trusted
+    let mut same_ids = ports.into_iter().filter(|p| {
+        p.vendor_id.is_some() && p.vendor_id == device.vendor_id && p.product_id == device.product_id
+    });
+    match (same_ids.next(), same_ids.next()) {
+        (Some(p), None) => Some(p.path),
+        _ => None,
+    }
+}
+
+/// Open a port with the given monitor settings
+fn open_port(port: &str, settings: &MonitorSettings) -> Result<Box<dyn SerialPort>, SerialError> {
+    let data_bits = match settings.data_bits {
+        5 => serialport::DataBits::Five,
+        6 => serialport::DataBits::Six,
+        7 => serialport::DataBits::Seven,
+        8 => serialport::DataBits::Eight,
+        n => return Err(SerialError::InvalidSettings(format!("{} data bits", n))),
+    };
+    let stop_bits = match settings.stop_bits {
+        1 => serialport::StopBits::One,
+        2 => serialport::StopBits::Two,
+        n => return Err(SerialError::InvalidSettings(format!("{} stop bits", n))),
+    };
+    let parity = match settings.parity {
+        Parity::None => serialport::Parity::None,
+        Parity::Odd => serialport::Parity::Odd,
+        Parity::Even => serialport::Parity::Even,
+    };
+
+    serialport::new(port, settings.baud_rate)
+        .data_bits(data_bits)
+        .stop_bits(stop_bits)
+        .parity(parity)
+        .flow_control(serialport::FlowControl::None)
+        .timeout(Duration::from_millis(READ_TIMEOUT_MS))
+        .open()
+        .map_err(|e| SerialError::OpenError(e.to_string()))
+}
+
+/// Spawn the background reader for a session
+/// Runs until `running` is cleared or the port fails (e.g. unplugged)
+fn spawn_reader(
+    app: AppHandle,
+    port: String,
+    id: u64,
+    mut serial: Box<dyn SerialPort>,
+    taps: Arc<Mutex<SessionTaps>>,
+    running: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buf = [0u8; 1024];
+        let mut pending = Vec::new();
+        let mut line = String::new();
+        let mut error = None;
+
+        while running.load(Ordering::SeqCst) {
+            match serial.read(&mut buf) {
+                Ok(0) => {}
+                Ok(n) => {
+                    pending.extend_from_slice(&buf[..n]);
+                    let data = take_utf8(&mut pending);
+                    if !data.is_empty() {
+                        feed_lines(&mut line, &data, &taps);
+                        let _ = app.emit(
+                            "serial-data",
+                            SerialData {
+                                port: port.clone(),
+                                data,
+                            },
+                        );
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    error = Some(e.to_string());
+                    break;
+                }
+            }
+
+            // Runs at least every read timeout, so frames go out at a steady rate
+            let frame = taps
+                .lock()
+                .unwrap()
+                .plotter
+                .as_mut()
+                .and_then(|p| p.take_frame(&port));
+            if let Some(frame) = frame {
+                let _ = app.emit("serial-plot", frame);
+            }
+        }
+
+        // Stopped on purpose: close_serial / claim report it themselves
+        let Some(error) = error else {
+            return;
+        };
+
+        // Closed by the port itself: drop the session
+        {
+            let sessions = app.state::<SerialSessions>();
+            let mut map = sessions.sessions.lock().unwrap();
+            if map.get(&port).is_some_and(|s| s.id == id) {
+                map.remove(&port);
+            }
+        }
+
+        let _ = app.emit(
+            "serial-closed",
+            SerialClosed {
+                port,
+                error: Some(error),
+            },
+        );
+    })
+}
+
+/// Split received text into lines and hand complete ones to the taps
+/// `line` holds the unterminated tail between reads
+fn feed_lines(line: &mut String, data: &str, taps: &Mutex<SessionTaps>) {
+    let mut taps = taps.lock().unwrap();
+
+    for c in data.chars() {
+        if c == '\n' || line.len() >= MAX_LINE_LEN {
+            let complete = std::mem::take(line);
+            let complete = complete.strip_suffix('\r').unwrap_or(&complete);
+            if let Some(plotter) = taps.plotter.as_mut() {
+                plotter.push_line(complete);
+            }
+            if let Some(recorder) = taps.recorder.as_mut() {
+                recorder.write_line(complete);
+            }
+        }
+        if c != '\n' {
+            line.push(c);
+        }
+    }
+}
+
+/// Decode the complete UTF-8 prefix of `pending`, keeping a trailing
+/// partial character for the next read
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => pending.len(),
+    };
+    let rest = pending.split_off(valid);
+    let text = String::from_utf8_lossy(pending).into_owned();
+    *pending = rest;
+    text
+}
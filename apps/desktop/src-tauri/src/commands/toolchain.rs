@@ -0,0 +1,537 @@
+/*!
+ * Build inspection with the bundled avr-gcc toolchain
+ * (packages/arduino/tools/avr-gcc/<version>/bin in the arduino data dir)
+ *
+ * - Symbol breakdown: avr-nm / avr-size on the ELF of the last build, grouped
+ *   into sketch, Arduino core and library code
+ * - Preprocessed sketch: the .ino.cpp arduino-cli generated (with prototypes)
+ * - Disassembly: avr-objdump -d -S, split per function
+ */
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use tokio::process::Command;
+
+use super::arduino::{get_data_dir, ArduinoError};
+use super::builds::Builds;
+
+/// Where a symbol's code comes from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolOrigin {
+    Sketch,
+    Core,
+    Library,
+    /// libc, libgcc and symbols without debug info
+    Other,
+}
+
+/// One sized symbol of the ELF
+#[derive(Debug, Clone, Serialize)]
+pub struct Symbol {
+    /// Demangled name
+    pub name: String,
+    /// "text", "data", "bss" or "rodata"
+    pub section: String,
+    pub size: u64,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// Symbols of one origin, largest first
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolGroup {
+    pub origin: SymbolOrigin,
+    /// "sketch", "core", "other", or the library name
+    pub name: String,
+    /// Bytes of program storage (text + data)
+    pub flash: u64,
+    /// Bytes of RAM (data + bss)
+    pub ram: u64,
+    pub symbols: Vec<Symbol>,
+}
+
+/// Section totals from avr-size
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionSize {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Memory breakdown of a build, groups largest first
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolReport {
+    pub elf: String,
+    pub sections: Vec<SectionSize>,
+    pub groups: Vec<SymbolGroup>,
+}
+
+/// Disassembly of one function, source lines interleaved
+#[derive(Debug, Clone, Serialize)]
+pub struct DisassembledFunction {
+    /// Demangled name
+    pub name: String,
+    pub address: String,
+    pub code: String,
+}
+
+/// Break down the flash/RAM usage of the last build per symbol
+#[tauri::command]
+pub async fn symbol_breakdown(
+    app: AppHandle,
+    builds: State<'_, Builds>,
+) -> Result<SymbolReport, ArduinoError> {
+    let elf = last_elf(&builds)?;
+
+    let nm = run_tool(
+        &app,
+        "avr-nm",
+        &["--print-size", "--size-sort", "--demangle", "--line-numbers"],
+        &elf,
+    )
+    .await?;
+    let size = run_tool(&app, "avr-size", &["-A"], &elf).await?;
+
+    let mut groups: Vec<SymbolGroup> = Vec::new();
+    for (symbol, origin, group_name) in nm.lines().filter_map(parse_nm_line) {
+        let group = match groups.iter_mut().position(|g| g.name == group_name) {
+            Some(i) => &mut groups[i],
+            None => {
+                groups.push(SymbolGroup {
+                    origin,
+                    name: group_name,
+                    flash: 0,
+                    ram: 0,
+                    symbols: Vec::new(),
+                });
+                groups.last_mut().unwrap()
+            }
+        };
+
+        match symbol.section.as_str() {
+            "data" => {
+                group.flash += symbol.size;
+                group.ram += symbol.size;
+            }
+            "bss" => group.ram += symbol.size,
+            _ => group.flash += symbol.size,
+        }
+        group.symbols.push(symbol);
+    }
+
+    for group in &mut groups {
+        group.symbols.sort_by_key(|s| std::cmp::Reverse(s.size));
+    }
+    groups.sort_by_key(|g| std::cmp::Reverse(g.flash + g.ram));
+
+    Ok(SymbolReport {
+        elf: elf.to_string_lossy().to_string(),
+        sections: parse_size_output(&size),
+        groups,
+    })
+}
+
+/// Preprocessed sketch of the last build, as compiled by gcc
+#[tauri::command]
+pub async fn preprocessed_source(builds: State<'_, Builds>) -> Result<String, ArduinoError> {
+    let build_dir = last_build(&builds)?;
+    let source = build_dir.join("sketch").join("sketch.ino.cpp");
+
+    tokio::fs::read_to_string(&source).await.map_err(|_| {
+        ArduinoError::ToolchainError(format!("No preprocessed sketch in {}", build_dir.display()))
+    })
+}
+
+/// Disassemble the last build, one entry per function in address order
+#[tauri::command]
+pub async fn disassemble(
+    app: AppHandle,
+    builds: State<'_, Builds>,
+) -> Result<Vec<DisassembledFunction>, ArduinoError> {
+    let elf = last_elf(&builds)?;
+    let output = run_tool(&app, "avr-objdump", &["-d", "-S", "--demangle"], &elf).await?;
+    Ok(split_functions(&output))
+}
+
+/// Output directory of the last build
+fn last_build(builds: &Builds) -> Result<PathBuf, ArduinoError> {
+    builds
+        .last()
+        .ok_or_else(|| ArduinoError::ToolchainError("No build yet, compile first".to_string()))
+}
+
+/// ELF file of the last build
+fn last_elf(builds: &Builds) -> Result<PathBuf, ArduinoError> {
+    let build_dir = last_build(builds)?;
+
+    std::fs::read_dir(&build_dir)?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| p.extension().is_some_and(|ext| ext == "elf"))
+        .ok_or_else(|| ArduinoError::ToolchainError(format!("No ELF file in {}", build_dir.display())))
+}
+
+/// Bin directory of the newest installed avr-gcc
+fn toolchain_bin(app: &AppHandle) -> Result<PathBuf, ArduinoError> {
+    let tools_dir = get_data_dir(app)?.join("packages/arduino/tools/avr-gcc");
+
+    let mut versions: Vec<PathBuf> = std::fs::read_dir(&tools_dir)
+        .map_err(|_| ArduinoError::ToolchainError("avr-gcc toolchain is not installed".to_string()))?
+        .flatten()
+        .map(|e| e.path().join("bin"))
+        .filter(|bin| bin.is_dir())
+        .collect();
+    versions.sort_by_cached_key(|bin| {
+        let version = bin.parent().and_then(|dir| dir.file_name()).unwrap_or_default();
+        version_key(&version.to_string_lossy())
+    });
+
+    versions
+        .pop()
+        .ok_or_else(|| ArduinoError::ToolchainError("avr-gcc toolchain is not installed".to_string()))
+}
+
+/// Sort key of a tool version ("7.3.0-atmel3.6.1-arduino7"): its runs of
+/// digits and letters between '.' and '-', numbers compared as numbers
+fn version_key(version: &str) -> Vec<(u64, String)> {
+    let mut key = Vec::new();
+    for part in version.split(['.', '-']) {
+        let mut rest = part;
+        while let Some(first) = rest.chars().next() {
+            let is_digit = first.is_ascii_digit();
+            let end = rest
+                .find(|c: char| c.is_ascii_digit() != is_digit)
+                .unwrap_or(rest.len());
+            let (run, tail) = rest.split_at(end);
+            key.push(match run.parse::<u64>() {
+                Ok(n) if is_digit => (n, String::new()),
+                _ => (0, run.to_string()),
+            });
+            rest = tail;
+        }
+    }
+    key
+}
+
+/// Run a toolchain binary on a file and return its stdout
+async fn run_tool(
+    app: &AppHandle,
+    tool: &str,
+    args: &[&str],
+    file: &Path,
+) -> Result<String, ArduinoError> {
+    let binary = if cfg!(windows) {
+        format!("{}.exe", tool)
+    } else {
+        tool.to_string()
+    };
+
+    let output = Command::new(toolchain_bin(app)?.join(binary))
+        .args(args)
+        .arg(file)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(ArduinoError::ToolchainError(format!(
+            "{} failed: {}",
+            tool,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse an avr-nm line: `address size type name[\tfile:line]`
+/// Returns the symbol with its origin and group name
+fn parse_nm_line(line: &str) -> Option<(Symbol, SymbolOrigin, String)> {
+    let (fields, location) = match line.split_once('\t') {
+        Some((fields, location)) => (fields, Some(location)),
+        None => (line, None),
+    };
+
+    let mut parts = fields.splitn(4, ' ');
+    let _address = parts.next()?;
+    let size = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let section = match parts.next()? {
+        "T" | "t" | "W" | "w" => "text",
+        // Weak objects are the vtables, which avr-gcc keeps in RAM
+        "D" | "d" | "V" | "v" => "data",
+        "B" | "b" => "bss",
+        "R" | "r" => "rodata",
+        _ => return None,
+    };
+    let name = parts.next()?.to_string();
+
+    let (file, line_no) = match location.and_then(|l| l.rsplit_once(':')) {
+        Some((file, line_no)) => (Some(file.replace('\\', "/")), line_no.parse().ok()),
+        None => (location.map(|l| l.replace('\\', "/")), None),
+    };
+    let (origin, group) = classify(file.as_deref());
+
+    Some((
+        Symbol {
+            name,
+            section: section.to_string(),
+            size,
+            file,
+            line: line_no,
+        },
+        origin,
+        group,
+    ))
+}
+
+/// Origin and group name of a source file
+fn classify(file: Option<&str>) -> (SymbolOrigin, String) {
+    let Some(file) = file else {
+        return (SymbolOrigin::Other, "other".to_string());
+    };
+
+    if file.contains("/sketch/") || file.ends_with(".ino") {
+        return (SymbolOrigin::Sketch, "sketch".to_string());
+    }
+    if let Some((_, rest)) = file.rsplit_once("/libraries/") {
+        let library = rest.split('/').next().unwrap_or(rest);
+        return (SymbolOrigin::Library, library.to_string());
+    }
+    if file.contains("/cores/") || file.contains("/variants/") {
+        return (SymbolOrigin::Core, "core".to_string());
+    }
+
+    (SymbolOrigin::Other, "other".to_string())
+}
+
+/// Parse `avr-size -A` output, `.section  size  addr` rows
+fn parse_size_output(output: &str) -> Vec<SectionSize> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?.strip_prefix('.')?;
+            let size = parts.next()?.parse().ok()?;
+            Some(SectionSize {
+                name: name.to_string(),
+                size,
+            })
+        })
+        .filter(|s| s.size > 0 && !s.name.starts_with("debug") && !s.name.starts_with("stab"))
+        .collect()
+}
+
+/// Split objdump output on function headers (`00000068 <setup>:`)
+fn split_functions(output: &str) -> Vec<DisassembledFunction> {
+    let mut functions: Vec<DisassembledFunction> = Vec::new();
+
+    for line in output.lines() {
+        if let Some((address, name)) = function_header(line) {
+            functions.push(DisassembledFunction {
+                name: name.to_string(),
+                address: address.to_string(),
+                code: String::new(),
+            });
+            continue;
+        }
+
+        // Lines before the first function are the file/section banner
+        if let Some(function) = functions.last_mut() {
+            if line.starts_with("Disassembly of section") {
+                continue;
+            }
+            function.code.push_str(line);
+            function.code.push('\n');
+        }
+    }
+
+    for function in &mut functions {
+        function.code = function.code.trim_end().to_string();
+    }
+    functions
+}
+
+fn function_header(line: &str) -> Option<(&str, &str)> {
+    let (address, rest) = line.split_once(' ')?;
+    if address.is_empty() || !address.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let name = rest.strip_prefix('<')?.strip_suffix(">:")?;
+    Some((address, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORE: &str =
+        "/home/user/.local/share/com.hduino.app/arduino/packages/arduino/hardware/avr/1.8.6";
+    const BUILD: &str = "/home/user/.local/share/com.hduino.app/builds/default-a1b2c3d4/arduino_avr_uno-0e5f4a7b/build";
+
+    #[test]
+    fn parses_nm_lines() {
+        let line = format!(
+            "000000a6 00000016 T setup\t{}/sketch/sketch.ino.cpp:12",
+            BUILD
+        );
+        let (symbol, origin, group) = parse_nm_line(&line).unwrap();
+        assert_eq!(symbol.name, "setup");
+        assert_eq!((symbol.section.as_str(), symbol.size), ("text", 0x16));
+        assert_eq!(symbol.line, Some(12));
+        assert_eq!((origin, group.as_str()), (SymbolOrigin::Sketch, "sketch"));
+
+        let line = format!(
+            "00800102 00000004 b timer0_millis\t{}/cores/arduino/wiring.c:47",
+            CORE
+        );
+        let (symbol, origin, group) = parse_nm_line(&line).unwrap();
+        assert_eq!((symbol.section.as_str(), symbol.size), ("bss", 4));
+        assert_eq!((origin, group.as_str()), (SymbolOrigin::Core, "core"));
+    }
+
+    #[test]
+    fn keeps_spaces_in_demangled_names() {
+        let line = format!(
+            "000001a6 00000094 T HardwareSerial::write(unsigned char)\t{}/cores/arduino/HardwareSerial.cpp:225",
+            CORE
+        );
+        let (symbol, _, _) = parse_nm_line(&line).unwrap();
+        assert_eq!(symbol.name, "HardwareSerial::write(unsigned char)");
+        assert_eq!(symbol.line, Some(225));
+
+        let (symbol, origin, _) =
+            parse_nm_line("00800100 00000012 V vtable for HardwareSerial").unwrap();
+        assert_eq!(symbol.name, "vtable for HardwareSerial");
+        assert_eq!(symbol.section, "data");
+        assert_eq!(symbol.file, None);
+        assert_eq!(origin, SymbolOrigin::Other);
+    }
+
+    #[test]
+    fn groups_libraries_by_name() {
+        let line = "00000240 0000004c T Servo::attach(int, int)\t/home/user/.local/share/com.hduino.app/arduino/libraries/Servo/src/avr/Servo.cpp:232";
+        let (_, origin, group) = parse_nm_line(line).unwrap();
+        assert_eq!((origin, group.as_str()), (SymbolOrigin::Library, "Servo"));
+
+        let line = format!(
+            "000002e4 00000030 t TwoWire::begin()\t{}/libraries/Wire/src/Wire.cpp:51",
+            CORE
+        );
+        let (_, origin, group) = parse_nm_line(&line).unwrap();
+        assert_eq!((origin, group.as_str()), (SymbolOrigin::Library, "Wire"));
+    }
+
+    #[test]
+    fn classifies_windows_paths() {
+        let line = "000000a6 00000016 T loop\tC:\\Users\\user\\AppData\\Roaming\\com.hduino.app\\builds\\default-a1b2c3d4\\arduino_avr_uno-0e5f4a7b\\build\\sketch\\sketch.ino.cpp:20";
+        let (symbol, origin, _) = parse_nm_line(line).unwrap();
+        assert_eq!(symbol.line, Some(20));
+        assert!(symbol.file.unwrap().starts_with("C:/Users/user/"));
+        assert_eq!(origin, SymbolOrigin::Sketch);
+    }
+
+    #[test]
+    fn skips_unsized_and_other_symbols() {
+        assert!(parse_nm_line("0000010e 00000004 T __udivmodsi4").is_some());
+        assert!(parse_nm_line("00000000 00000002 a __tmp_reg__").is_none());
+        assert!(parse_nm_line("         U __do_copy_data").is_none());
+        assert!(parse_nm_line("").is_none());
+    }
+
+    #[test]
+    fn parses_size_output() {
+        let output = "\
+/tmp/build/sketch.ino.elf  :
+section                     size      addr
+.data                         22   8388864
+.text                       1478         0
+.bss                         157   8388886
+.comment                      17         0
+.note.gnu.avr.deviceinfo      64         0
+.debug_aranges              1384         0
+.debug_info                22578         0
+Total                      25700
+";
+        let sections: Vec<(String, u64)> = parse_size_output(output)
+            .into_iter()
+            .map(|s| (s.name, s.size))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                ("data".to_string(), 22),
+                ("text".to_string(), 1478),
+                ("bss".to_string(), 157),
+                ("comment".to_string(), 17),
+                ("note.gnu.avr.deviceinfo".to_string(), 64),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_disassembly_per_function() {
+        let output = "
+/tmp/build/sketch.ino.elf:     file format elf32-avr
+
+
+Disassembly of section .text:
+
+00000000 <__vectors>:
+   0:	0c 94 5c 00 	jmp	0xb8	; 0xb8 <__ctors_end>
+   4:	0c 94 6e 00 	jmp	0xdc	; 0xdc <__bad_interrupt>
+
+000000a6 <setup>:
+void setup() {
+  pinMode(13, OUTPUT);
+  a6:	61 e0       	ldi	r22, 0x01	; 1
+  a8:	8d e0       	ldi	r24, 0x0D	; 13
+  aa:	0c 94 a3 00 	jmp	0x146	; 0x146 <pinMode>
+
+000001a6 <HardwareSerial::write(unsigned char)>:
+ 1a6:	cf 93       	push	r28
+";
+        let functions = split_functions(output);
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["__vectors", "setup", "HardwareSerial::write(unsigned char)"]
+        );
+
+        assert_eq!(functions[1].address, "000000a6");
+        assert!(functions[1]
+            .code
+            .starts_with("void setup() {\n  pinMode(13, OUTPUT);\n"));
+        assert!(functions[1].code.ends_with("<pinMode>"));
+        assert_eq!(functions[2].code, " 1a6:\tcf 93       \tpush\tr28");
+    }
+
+    #[test]
+    fn picks_newest_toolchain_version() {
+        let mut versions = vec![
+            "7.3.0-atmel3.6.1-arduino7",
+            "5.4.0-atmel3.6.1-arduino2",
+            "7.3.0-atmel3.6.1-arduino10",
+            "4.8.1-arduino5",
+        ];
+        versions.sort_by_cached_key(|v| version_key(v));
+        assert_eq!(
+            versions,
+            [
+                "4.8.1-arduino5",
+                "5.4.0-atmel3.6.1-arduino2",
+                "7.3.0-atmel3.6.1-arduino7",
+                "7.3.0-atmel3.6.1-arduino10",
+            ]
+        );
+    }
+
+    #[test]
+    fn compares_version_numbers_as_numbers() {
+        assert!(version_key("1.10.0") > version_key("1.9.2"));
+        assert!(version_key("7.3.0") > version_key("7.3"));
+        assert_eq!(
+            version_key("arduino10"),
+            [(0, "arduino".to_string()), (10, String::new())]
+        );
+    }
+}
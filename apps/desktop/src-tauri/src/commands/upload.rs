@@ -0,0 +1,413 @@
+/*!
+ * Upload tool output parsing
+ * Follows the progress printed by the uploader arduino-cli runs, and picks
+ * the bytes written and verify result from its summary
+ *
+ * - avrdude: `Writing | ######` / `Reading | ######` bars, one # per 2%,
+ *   "924 bytes of flash written", "924 bytes of flash verified"
+ * - esptool: "Writing at 0x00010000... (42 %)", from 0 again for each image,
+ *   "Wrote 262144 bytes (...) at 0x00010000", "Hash of data verified."
+ * - bossac: "[=====     ] 40% (64/160 pages)",
+ *   "Write 11264 bytes to flash (176 pages)", "Verify successful"
+ */
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Summary of a finished upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadReport {
+    /// None when the uploader doesn't print it
+    pub bytes_written: Option<u64>,
+    /// None when the uploader didn't verify
+    pub verified: Option<bool>,
+    pub elapsed_ms: u64,
+}
+
+/// Phase of the upload, writing then reading back to verify
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Writing,
+    Verifying,
+}
+
+/// A progress bar line, by the uploader that drew it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bar {
+    /// avrdude "Writing |"
+    AvrdudeWrite(u8),
+    /// avrdude "Reading |", the signature check before writing or the verify
+    AvrdudeRead(u8),
+    /// esptool, from 0 again for each image
+    Esptool(u8),
+    /// bossac, drawn once for writing and again when verifying
+    Bossac(u8),
+}
+
+/// Tracks upload progress across output lines
+pub(crate) struct UploadProgress {
+    /// Overall percent range of the upload in the job progress
+    start: u8,
+    end: u8,
+    /// Last reported percent, events are only sent on change and it never
+    /// goes back
+    last: Option<u8>,
+    /// A write bar was drawn, later read bars are the verify
+    written: bool,
+    /// bossac printed "Verify", its next bars are the verify
+    bossac_verify: bool,
+    /// esptool images from the command line, and the one being written
+    images: Option<u32>,
+    image: u32,
+    bytes_written: Option<u64>,
+    verified: Option<bool>,
+}
+
+impl UploadProgress {
+    pub(crate) fn new(start: u8, end: u8) -> Self {
+        UploadProgress {
+            start,
+            end,
+            last: None,
+            written: false,
+            bossac_verify: false,
+            images: None,
+            image: 0,
+            bytes_written: None,
+            verified: None,
+        }
+    }
+
+    /// Feed a line or partial line of uploader output
+    /// Returns the new job percent and a message when progress moved
+    pub(crate) fn feed(&mut self, text: &str, complete: bool) -> Option<(u8, &'static str)> {
+        if complete {
+            self.read_summary(text);
+        }
+
+        let (phase, percent) = match parse_progress(text)? {
+            Bar::AvrdudeWrite(percent) => {
+                self.written = true;
+                (Phase::Writing, percent)
+            }
+            // Before any write it's the signature check, not progress
+            Bar::AvrdudeRead(_) if !self.written => return None,
+            Bar::AvrdudeRead(percent) => (Phase::Verifying, percent),
+            Bar::Esptool(percent) => (Phase::Writing, self.esptool_percent(percent)),
+            Bar::Bossac(percent) if self.bossac_verify => (Phase::Verifying, percent),
+            Bar::Bossac(percent) => (Phase::Writing, percent),
+        };
+
+        // Writing takes the first 3/4 of the range, verifying the rest
+        let span = u32::from(self.end - self.start);
+        let write_span = span * 3 / 4;
+        let offset = match phase {
+            Phase::Writing => write_span * u32::from(percent) / 100,
+            Phase::Verifying => write_span + (span - write_span) * u32::from(percent) / 100,
+        };
+        let overall = self.start + offset as u8;
+
+        if self.last.is_some_and(|last| overall <= last) {
+            return None;
+        }
+        self.last = Some(overall);
+
+        let message = match phase {
+            Phase::Writing => "Writing to the board...",
+            Phase::Verifying => "Verifying...",
+        };
+        Some((overall, message))
+    }
+
+    /// Percent of all the images written, each image gets an equal share
+    fn esptool_percent(&self, percent: u8) -> u8 {
+        let Some(images) = self.images else {
+            return percent;
+        };
+        let image = self.image.min(images - 1);
+        ((image * 100 + u32::from(percent)) / images) as u8
+    }
+
+    pub(crate) fn finish(self, elapsed: Duration) -> UploadReport {
+        UploadReport {
+            bytes_written: self.bytes_written,
+            verified: self.verified,
+            elapsed_ms: elapsed.as_millis() as u64,
+        }
+    }
+
+    fn read_summary(&mut self, line: &str) {
+        let line = line.trim().trim_start_matches("avrdude: ");
+
+        if line.contains("write_flash") || line.contains("write-flash") {
+            self.images = flash_images(line);
+        } else if line.starts_with("Verify ") && line.contains("bytes") {
+            // bossac: "Verify 11264 bytes of flash"
+            self.bossac_verify = true;
+        }
+
+        let written = line.ends_with("bytes of flash written")
+            || line.starts_with("Wrote ")
+            || (line.starts_with("Write ") && line.contains("bytes to flash"));
+        if written {
+            let bytes = line.split_whitespace().find_map(|word| word.parse::<u64>().ok());
+            if let Some(bytes) = bytes {
+                // esptool prints one "Wrote" per image (bootloader, partitions, app)
+                self.bytes_written = Some(self.bytes_written.unwrap_or(0) + bytes);
+            }
+            if line.contains(" at 0x") {
+                self.image += 1;
+            }
+        } else if line.ends_with("bytes of flash verified")
+            || line.starts_with("Hash of data verified")
+            || line.starts_with("Verify successful")
+        {
+            self.verified = Some(true);
+        } else if line.contains("verification error")
+            || line.contains("does not match")
+            || line.starts_with("Verify failed")
+        {
+            self.verified = Some(false);
+        }
+    }
+}
+
+/// Progress bar and percent (0-100) of a progress line
+fn parse_progress(text: &str) -> Option<Bar> {
+    let text = text.trim();
+
+    // avrdude: "Writing | ####" while drawing, "Writing | #### | 100% 0.52s" at the end
+    for (header, bar) in [
+        ("Writing |", Bar::AvrdudeWrite as fn(u8) -> Bar),
+        ("Reading |", Bar::AvrdudeRead),
+    ] {
+        if let Some(hashes) = text.strip_prefix(header) {
+            let hashes = hashes.split('|').next().unwrap_or(hashes);
+            let percent = hashes.chars().filter(|c| *c == '#').count() * 2;
+            return Some(bar(percent.min(100) as u8));
+        }
+    }
+
+    // esptool: "Writing at 0x00010000... (42 %)"
+    if text.starts_with("Writing at 0x") {
+        let percent = text.rsplit_once('(')?.1.split_whitespace().next()?;
+        return Some(Bar::Esptool(percent.parse::<u8>().ok()?.min(100)));
+    }
+
+    // bossac: "[=====     ] 40% (64/160 pages)"
+    if text.starts_with('[') {
+        let (_, rest) = text.split_once(']')?;
+        let percent = rest.trim_start().split('%').next()?;
+        return Some(Bar::Bossac(percent.parse::<u8>().ok()?.min(100)));
+    }
+
+    None
+}
+
+/// Number of images in an esptool command line,
+/// "write_flash -z ... 0x1000 boot.bin 0x8000 part.bin 0x10000 app.bin"
+fn flash_images(line: &str) -> Option<u32> {
+    let (_, args) = line
+        .split_once("write_flash")
+        .or_else(|| line.split_once("write-flash"))?;
+    let count = args
+        .split_whitespace()
+        .filter(|arg| arg.trim_matches('"').starts_with("0x"))
+        .count() as u32;
+    (count > 0).then_some(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed complete lines, returning the percents reported
+    fn feed(progress: &mut UploadProgress, lines: &[&str]) -> Vec<u8> {
+        lines
+            .iter()
+            .filter_map(|line| progress.feed(line, true))
+            .map(|(percent, _)| percent)
+            .collect()
+    }
+
+    #[test]
+    fn parses_progress_bars() {
+        assert_eq!(
+            parse_progress("Writing | #########"),
+            Some(Bar::AvrdudeWrite(18))
+        );
+        assert_eq!(
+            parse_progress(
+                "Reading | ################################################## | 100% 0.13s"
+            ),
+            Some(Bar::AvrdudeRead(100))
+        );
+        assert_eq!(
+            parse_progress("Writing at 0x00010000... (14 %)"),
+            Some(Bar::Esptool(14))
+        );
+        assert_eq!(
+            parse_progress("[=========                     ] 30% (53/176 pages)"),
+            Some(Bar::Bossac(30))
+        );
+        assert_eq!(parse_progress("avrdude: writing flash (924 bytes):"), None);
+        assert_eq!(parse_progress("Writing at 0x00010000..."), None);
+    }
+
+    #[test]
+    fn avrdude_signature_read_is_not_progress() {
+        let mut progress = UploadProgress::new(0, 100);
+        let percents = feed(
+            &mut progress,
+            &[
+                "avrdude: AVR device initialized and ready to accept instructions",
+                "Reading | ################################################## | 100% 0.00s",
+                "avrdude: Device signature = 0x1e950f (probably m328p)",
+                "avrdude: writing flash (924 bytes):",
+                "Writing | ################################################## | 100% 0.16s",
+                "avrdude: 924 bytes of flash written",
+                "avrdude: reading on-chip flash data:",
+                "Reading | ################################################## | 100% 0.13s",
+                "avrdude: 924 bytes of flash verified",
+                "avrdude done.  Thank you.",
+            ],
+        );
+        assert_eq!(percents, [75, 100]);
+
+        let report = progress.finish(Duration::from_millis(1500));
+        assert_eq!(report.bytes_written, Some(924));
+        assert_eq!(report.verified, Some(true));
+        assert_eq!(report.elapsed_ms, 1500);
+    }
+
+    #[test]
+    fn avrdude_partial_bars_move_progress() {
+        let mut progress = UploadProgress::new(40, 100);
+        assert_eq!(
+            progress.feed("Writing | ", false),
+            Some((40, "Writing to the board..."))
+        );
+        assert_eq!(
+            progress.feed("Writing | #########################", false),
+            Some((62, "Writing to the board..."))
+        );
+        // Redrawn with no change
+        assert_eq!(
+            progress.feed("Writing | #########################", false),
+            None
+        );
+        assert_eq!(
+            progress.feed("Reading | #########################", false),
+            Some((92, "Verifying..."))
+        );
+    }
+
+    #[test]
+    fn progress_never_goes_back() {
+        let mut progress = UploadProgress::new(0, 100);
+        let percents = feed(
+            &mut progress,
+            &[
+                "Writing at 0x00010000... (50 %)",
+                "Writing at 0x00010000... (10 %)",
+                "Writing at 0x00010000... (60 %)",
+            ],
+        );
+        assert_eq!(percents, [37, 45]);
+    }
+
+    #[test]
+    fn esptool_images_share_the_progress() {
+        let mut progress = UploadProgress::new(0, 100);
+        let percents = feed(
+            &mut progress,
+            &[
+                "\"/home/user/.arduino15/packages/esp32/tools/esptool_py/4.5.1/esptool\" --chip esp32 --port \"/dev/ttyUSB0\" --baud 921600 --before default_reset --after hard_reset write_flash -z --flash_mode dio --flash_freq 80m --flash_size 4MB 0x1000 \"/tmp/build/sketch.ino.bootloader.bin\" 0x8000 \"/tmp/build/sketch.ino.partitions.bin\" 0xe000 \"/home/user/.arduino15/packages/esp32/hardware/esp32/2.0.11/tools/partitions/boot_app0.bin\" 0x10000 \"/tmp/build/sketch.ino.bin\"",
+                "Compressed 17568 bytes to 12204...",
+                "Writing at 0x00001000... (100 %)",
+                "Wrote 17568 bytes (12204 compressed) at 0x00001000 in 0.4 seconds (effective 377.3 kbit/s)...",
+                "Hash of data verified.",
+                "Compressed 3072 bytes to 146...",
+                "Writing at 0x00008000... (100 %)",
+                "Wrote 3072 bytes (146 compressed) at 0x00008000 in 0.0 seconds (effective 599.1 kbit/s)...",
+                "Hash of data verified.",
+                "Compressed 8192 bytes to 47...",
+                "Writing at 0x0000e000... (100 %)",
+                "Wrote 8192 bytes (47 compressed) at 0x0000e000 in 0.1 seconds (effective 858.2 kbit/s)...",
+                "Hash of data verified.",
+                "Compressed 262144 bytes to 145034...",
+                "Writing at 0x00010000... (11 %)",
+                "Writing at 0x00028000... (55 %)",
+                "Writing at 0x00040000... (100 %)",
+                "Wrote 262144 bytes (145034 compressed) at 0x00010000 in 2.3 seconds (effective 916.1 kbit/s)...",
+                "Hash of data verified.",
+                "Hard resetting via RTS pin...",
+            ],
+        );
+        assert_eq!(percents, [18, 37, 56, 57, 66, 75]);
+
+        let report = progress.finish(Duration::ZERO);
+        assert_eq!(report.bytes_written, Some(17568 + 3072 + 8192 + 262144));
+        assert_eq!(report.verified, Some(true));
+    }
+
+    #[test]
+    fn counts_flash_images() {
+        assert_eq!(
+            flash_images("esptool.py write_flash 0x0 /tmp/build/sketch.ino.bin"),
+            Some(1)
+        );
+        assert_eq!(
+            flash_images("esptool write-flash -z 0x1000 boot.bin 0x8000 part.bin 0x10000 app.bin"),
+            Some(3)
+        );
+        assert_eq!(
+            flash_images("esptool write_flash -z --flash_mode dio"),
+            None
+        );
+    }
+
+    #[test]
+    fn bossac_verify_bars() {
+        let mut progress = UploadProgress::new(0, 100);
+        let percents = feed(
+            &mut progress,
+            &[
+                "Erase flash",
+                "done in 0.803 seconds",
+                "Write 11264 bytes to flash (176 pages)",
+                "[==============                ] 50% (88/176 pages)",
+                "[==============================] 100% (176/176 pages)",
+                "done in 0.058 seconds",
+                "Verify 11264 bytes of flash",
+                "[==============================] 100% (176/176 pages)",
+                "Verify successful",
+                "done in 0.012 seconds",
+            ],
+        );
+        assert_eq!(percents, [37, 75, 100]);
+
+        let report = progress.finish(Duration::ZERO);
+        assert_eq!(report.bytes_written, Some(11264));
+        assert_eq!(report.verified, Some(true));
+    }
+
+    #[test]
+    fn reads_verify_failures() {
+        let mut progress = UploadProgress::new(0, 100);
+        feed(
+            &mut progress,
+            &[
+                "avrdude: 924 bytes of flash written",
+                "avrdude: verification error, first mismatch at byte 0x0000",
+                "         0x0c != 0xff",
+            ],
+        );
+        assert_eq!(progress.finish(Duration::ZERO).verified, Some(false));
+
+        let mut progress = UploadProgress::new(0, 100);
+        feed(&mut progress, &["Hard resetting via RTS pin..."]);
+        let report = progress.finish(Duration::ZERO);
+        assert_eq!((report.bytes_written, report.verified), (None, None));
+    }
+}
@@ -10,6 +10,10 @@ fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(commands::serial::SerialSessions::default())
+        .manage(commands::recording::Replays::default())
+        .manage(commands::jobs::Jobs::default())
+        .manage(commands::builds::Builds::default())
         .setup(|app| {
             // Track splash start time for minimum display duration
             let splash_start = Instant::now();
@@ -44,6 +48,9 @@ fn main() {
                 let _ = splash_window.close();
             }
 
+            // Watch for boards being plugged in / pulled out
+            commands::serial::start_port_watcher(app.handle().clone());
+
             // Handle main window close event - quit the entire app
             let app_handle = app.handle().clone();
             main_window.on_window_event(move |event| {
@@ -84,10 +91,26 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::serial::list_ports,
+            commands::serial::open_serial,
+            commands::serial::close_serial,
+            commands::serial::write_serial,
+            commands::serial::set_serial_signals,
+            commands::serial::reset_board,
+            commands::serial::touch_1200bps,
+            commands::serial::detect_baud_rate,
+            commands::serial::sketch_baud_rate,
+            commands::plotter::start_plotter,
+            commands::plotter::stop_plotter,
+            commands::recording::start_recording,
+            commands::recording::stop_recording,
+            commands::recording::replay_recording,
+            commands::recording::stop_replay,
             commands::files::save_file_dialog,
             commands::files::open_file_dialog,
+            commands::files::pick_library_dialog,
             commands::arduino::compile_code,
             commands::arduino::upload_code,
+            commands::arduino::upload_build,
             commands::arduino::check_arduino_cli,
             commands::arduino::get_arduino_cli_version,
             commands::arduino::list_installed_boards,
@@ -96,6 +119,25 @@ fn main() {
             commands::arduino::install_core,
             commands::arduino::search_cores,
             commands::arduino::get_bundled_cores,
+            commands::libraries::get_bundled_libraries,
+            commands::libraries::list_libraries,
+            commands::libraries::search_libraries,
+            commands::libraries::install_library,
+            commands::libraries::upgrade_library,
+            commands::libraries::uninstall_library,
+            commands::libraries::install_local_library,
+            commands::includes::check_includes,
+            commands::boards::detect_board,
+            commands::jobs::cancel_job,
+            commands::jobs::list_jobs,
+            commands::jobs::get_job_timeouts,
+            commands::jobs::set_job_timeouts,
+            commands::toolchain::symbol_breakdown,
+            commands::toolchain::preprocessed_source,
+            commands::toolchain::disassemble,
+            commands::builds::list_builds,
+            commands::builds::get_builds_size,
+            commands::builds::purge_builds,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
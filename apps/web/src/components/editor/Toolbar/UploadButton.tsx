@@ -68,7 +68,11 @@ export function UploadButton() {
     setErrorMessage('');
 
     try {
-      const result = await upload(selectedPort, generatedCode, boardProfile.compilerFlag);
+      // Keeps a build per project, and explains errors in the system language
+      const result = await upload(selectedPort, generatedCode, boardProfile.compilerFlag, {
+        project: currentProject.id,
+        lang: navigator.language,
+      });
 
       if (result.success) {
         setStatus('success');
@@ -88,7 +92,8 @@ export function UploadButton() {
 
   // Update status based on progress from useUpload hook
   const currentStatus: UploadStatus =
-    progress.stage === 'compiling' ? 'compiling' :
+    progress.stage === 'initializing' || progress.stage === 'compiling' || progress.stage === 'linking'
+      ? 'compiling' :
     progress.stage === 'uploading' ? 'uploading' :
     status;
 
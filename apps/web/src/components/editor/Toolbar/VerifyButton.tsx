@@ -60,7 +60,11 @@ export function VerifyButton() {
 
     try {
       // Compile the code
-      const result = await adapter.compile(generatedCode, boardProfile.compilerFlag);
+      // Keeps a build per project, and explains errors in the system language
+      const result = await adapter.compile(generatedCode, boardProfile.compilerFlag, {
+        project: currentProject.id,
+        lang: navigator.language,
+      });
 
       if (result.success) {
         setStatus('success');
@@ -1,13 +1,18 @@
 'use client';
 
 import { useState, useEffect, useCallback } from 'react';
-import { getAdapter, type UploadResult } from '@hduino/platform';
+import {
+  getAdapter,
+  type CompileOptions,
+  type JobStage,
+  type UploadResult,
+} from '@hduino/platform';
 
 /**
  * Upload progress state
  */
 export interface UploadProgress {
-  stage: 'compiling' | 'uploading' | 'idle';
+  stage: JobStage | 'idle';
   percent: number;
   message?: string;
 }
@@ -26,7 +31,12 @@ export interface UseUploadState {
  * Upload hook return type
  */
 export interface UseUploadReturn extends UseUploadState {
-  upload: (port: string, code: string, board: string) => Promise<UploadResult>;
+  upload: (
+    port: string,
+    code: string,
+    board: string,
+    options?: CompileOptions
+  ) => Promise<UploadResult>;
   reset: () => void;
   canUpload: boolean;
 }
@@ -65,53 +75,16 @@ export function useUpload(): UseUploadReturn {
     setCanUpload(capabilities.canUpload);
   }, []);
 
-  // Listen for Tauri events for progress updates
-  useEffect(() => {
-    // Only set up listener if we're in Tauri and uploading
-    if (typeof window === 'undefined') return;
-
-    const tauri = (window as any).__TAURI__;
-    if (!tauri?.event?.listen) return;
-
-    let unlisten: (() => void) | undefined;
-
-    const setupListener = async () => {
-      unlisten = await tauri.event.listen(
-        'compile-progress',
-        (event: { payload: { stage: string; percent: number; message: string } }) => {
-          const { stage, percent, message } = event.payload;
-          // Only update progress if we're actually uploading
-          setState((prev) => {
-            if (!prev.isUploading) {
-              return prev; // Ignore events when not uploading
-            }
-            return {
-              ...prev,
-              progress: {
-                stage: stage as 'compiling' | 'uploading',
-                percent,
-                message,
-              },
-            };
-          });
-        }
-      );
-    };
-
-    setupListener();
-
-    return () => {
-      if (unlisten) {
-        unlisten();
-      }
-    };
-  }, []);
-
   /**
    * Upload code to Arduino
    */
   const upload = useCallback(
-    async (port: string, code: string, board: string): Promise<UploadResult> => {
+    async (
+      port: string,
+      code: string,
+      board: string,
+      options?: CompileOptions
+    ): Promise<UploadResult> => {
       const adapter = getAdapter();
 
       setState((prev) => ({
@@ -123,12 +96,18 @@ export function useUpload(): UseUploadReturn {
       }));
 
       try {
-        const result = await adapter.upload(port, code, board, (stage, percent) => {
-          setState((prev) => ({
-            ...prev,
-            progress: { stage, percent },
-          }));
-        });
+        const result = await adapter.upload(
+          port,
+          code,
+          board,
+          (stage, percent, message) => {
+            setState((prev) => ({
+              ...prev,
+              progress: { stage, percent, message },
+            }));
+          },
+          options
+        );
 
         setState((prev) => ({
           ...prev,
@@ -10,9 +10,43 @@ export type {
   UploadResult,
   PlatformCapabilities,
   UploadProgressCallback,
+  JobStage,
   CoreInfo,
+  LibraryInfo,
+  BundledLibrary,
+  LocalLibrary,
+  MissingInclude,
   BoardInfo,
+  BoardCandidate,
   CoreStatus,
+  CompilerDiagnostic,
+  ErrorHint,
+  CompileOptions,
+  SourceMapEntry,
+  MonitorSettings,
+  SerialDataEvent,
+  SerialClosedEvent,
+  Unlisten,
+  SerialPausedEvent,
+  SerialResumedEvent,
+  ResetMethod,
+  PlotterSettings,
+  PlotFrameEvent,
+  RecordFormat,
+  RecordingSummary,
+  BaudDetection,
+  JobProgressEvent,
+  JobTimeouts,
+  CompileReport,
+  UploadReport,
+  BuildLogEvent,
+  MemoryUsage,
+  BuildSymbol,
+  SymbolReport,
+  DisassembledFunction,
+  BuildEntry,
+  BuildsSize,
+  PurgeSummary,
 } from './types';
 
 // Export platform detection
@@ -11,16 +11,88 @@ import type {
   UploadProgressCallback,
   CoreInfo,
   BoardInfo,
+  BoardCandidate,
   CoreStatus,
+  CompileOptions,
+  MonitorSettings,
+  SerialDataEvent,
+  SerialClosedEvent,
+  Unlisten,
+  SerialPausedEvent,
+  SerialResumedEvent,
+  ResetMethod,
+  PlotterSettings,
+  PlotFrameEvent,
+  RecordFormat,
+  RecordingSummary,
+  BaudDetection,
+  JobProgressEvent,
+  JobTimeouts,
+  BuildLogEvent,
+  CompileReport,
+  LibraryInfo,
+  BundledLibrary,
+  MissingInclude,
+  LocalLibrary,
+  BuildSymbol,
+  SymbolReport,
+  DisassembledFunction,
+  BuildEntry,
+  BuildsSize,
+  PurgeSummary,
 } from './types';
 
+let jobCounter = 0;
+
+/**
+ * New job ID, used to cancel a job and to filter its progress events
+ */
+function newJobId(kind: string): string {
+  jobCounter += 1;
+  return `${kind}-${Date.now()}-${jobCounter}`;
+}
+
 /**
- * Compile progress event from Rust backend
+ * Failed result from a compile/upload command error
+ * Compile failures are objects with the log as `message`, next to the parsed
+ * diagnostics and hints, and `"kind": "missing-library"` for a missing header
  */
-interface CompileProgressEvent {
-  stage: string;
-  percent: number;
-  message: string;
+function failedResult(error: unknown, stage?: 'compile' | 'upload'): UploadResult {
+  // Extract error message from Tauri error
+  let errorMessage = 'Unknown error';
+  const err = error && typeof error === 'object' ? (error as any) : undefined;
+
+  if (typeof error === 'string') {
+    // Simple string error
+    errorMessage = error;
+  } else if (err) {
+    // Try different error properties that Tauri might use
+    if (err.message) {
+      errorMessage = err.message;
+    } else if (err.kind === 'missing-library') {
+      // Found before compiling, there is no compiler log
+      errorMessage = `No installed library provides ${err.header}`;
+    } else if (err.error) {
+      errorMessage = err.error;
+    } else if (err.toString && err.toString() !== '[object Object]') {
+      errorMessage = err.toString();
+    } else {
+      // Last resort: stringify the entire object
+      errorMessage = JSON.stringify(error, null, 2);
+    }
+  }
+
+  return {
+    success: false,
+    // Only compile failures come with diagnostics or a missing header
+    stage: stage ?? (err?.diagnostics || err?.kind ? 'compile' : undefined),
+    error: errorMessage,
+    diagnostics: err?.diagnostics,
+    hints: err?.hints,
+    missing_include: err?.kind === 'missing-library'
+      ? { header: err.header, candidates: err.candidates ?? [] }
+      : null,
+  };
 }
 
 /**
@@ -65,14 +137,29 @@ async function invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T
   return invokeFn<T>(cmd, args);
 }
 
+/**
+ * Listen to a backend event, returns the function that stops listening
+ */
+async function listen<T>(event: string, handler: (payload: T) => void): Promise<Unlisten> {
+  const tauri = (window as any).__TAURI__;
+  if (!tauri?.event?.listen) {
+    return () => {};
+  }
+  return await tauri.event.listen(event, (e: { payload: T }) => handler(e.payload));
+}
+
 /**
  * Serial port info returned from Rust
  */
 interface TauriSerialPortInfo {
   path: string;
+  port_type: 'usb' | 'pci' | 'bluetooth' | 'unknown';
   manufacturer: string | null;
+  product: string | null;
+  serial_number: string | null;
   vendor_id: string | null;
   product_id: string | null;
+  interface: number | null;
 }
 
 /**
@@ -95,7 +182,7 @@ export class TauriAdapter implements DeviceAdapter {
     return {
       canListPorts: true,
       canUpload: true, // Enabled via Arduino CLI integration (Phase 8)
-      canAutoDetectBoard: false,
+      canAutoDetectBoard: true,
       supportsProgress: true,
     };
   }
@@ -103,15 +190,19 @@ export class TauriAdapter implements DeviceAdapter {
   /**
    * List available serial ports using native serialport library
    */
-  async listPorts(): Promise<SerialPort[]> {
+  async listPorts(includeAll = false): Promise<SerialPort[]> {
     try {
-      const ports = await invoke<TauriSerialPortInfo[]>('list_ports');
+      const ports = await invoke<TauriSerialPortInfo[]>('list_ports', { includeAll });
 
       return ports.map((port) => ({
         path: port.path,
+        portType: port.port_type,
         manufacturer: port.manufacturer ?? undefined,
+        product: port.product ?? undefined,
+        serialNumber: port.serial_number ?? undefined,
         vendorId: port.vendor_id ?? undefined,
         productId: port.product_id ?? undefined,
+        interface: port.interface ?? undefined,
       }));
     } catch (error) {
       console.error('Failed to list serial ports:', error);
@@ -122,53 +213,30 @@ export class TauriAdapter implements DeviceAdapter {
   /**
    * Verify/compile code without uploading
    */
-  async compile(code: string, board: string): Promise<UploadResult> {
+  async compile(
+    code: string,
+    board: string,
+    options?: CompileOptions,
+    jobId?: string
+  ): Promise<UploadResult> {
     try {
-      const result = await invoke<string>('compile_code', {
+      const report = await invoke<CompileReport>('compile_code', {
         code,
         board,
+        options: options ?? null,
+        jobId: jobId ?? newJobId('compile'),
       });
 
       return {
         success: true,
         stage: 'compile',
-        message: 'Compilation successful!\n\nBuild output: ' + result,
+        message: 'Compilation successful!\n\nBuild output: ' + report.build_path,
+        report,
       };
     } catch (error) {
       // Log the full error object for debugging
       console.error('Compilation failed - Full error:', error);
-      console.error('Error type:', typeof error);
-      console.error('Error keys:', error && typeof error === 'object' ? Object.keys(error) : 'N/A');
-
-      // Extract error message from Tauri error
-      let errorMessage = 'Unknown compilation error';
-
-      if (typeof error === 'string') {
-        // Simple string error
-        errorMessage = error;
-      } else if (error && typeof error === 'object') {
-        // Try different error properties that Tauri might use
-        const err = error as any;
-
-        if (err.message) {
-          errorMessage = err.message;
-        } else if (err.error) {
-          errorMessage = err.error;
-        } else if (err.toString && err.toString() !== '[object Object]') {
-          errorMessage = err.toString();
-        } else {
-          // Last resort: stringify the entire object
-          errorMessage = JSON.stringify(error, null, 2);
-        }
-      }
-
-      console.error('Extracted error message:', errorMessage);
-
-      return {
-        success: false,
-        stage: 'compile',
-        error: errorMessage,
-      };
+      return failedResult(error, 'compile');
     }
   }
 
@@ -180,40 +248,52 @@ export class TauriAdapter implements DeviceAdapter {
     port: string,
     code: string,
     board: string,
-    onProgress?: UploadProgressCallback
+    onProgress?: UploadProgressCallback,
+    options?: CompileOptions,
+    jobId: string = newJobId('upload')
+  ): Promise<UploadResult> {
+    return await this.runUpload('upload_code', { port, code, board, options }, onProgress, jobId);
+  }
+
+  /**
+   * Upload a build made by compile or upload without recompiling
+   * Fails if the build was recompiled or purged since
+   */
+  async uploadBuild(
+    buildId: string,
+    port: string,
+    onProgress?: UploadProgressCallback,
+    options?: CompileOptions,
+    jobId: string = newJobId('upload')
+  ): Promise<UploadResult> {
+    return await this.runUpload('upload_build', { buildId, port, options }, onProgress, jobId);
+  }
+
+  /**
+   * Run an upload command, forwarding its job's progress events
+   */
+  private async runUpload(
+    command: 'upload_code' | 'upload_build',
+    args: Record<string, unknown>,
+    onProgress: UploadProgressCallback | undefined,
+    jobId: string
   ): Promise<UploadResult> {
     // Set up event listener for progress updates
-    let unlisten: (() => void) | undefined;
+    let unlisten: Unlisten | undefined;
 
     try {
-      // Listen for compile-progress events from Rust backend
+      // Listen for this job's progress events from Rust backend
       if (onProgress) {
-        const tauri = (window as any).__TAURI__;
-        if (tauri?.event?.listen) {
-          unlisten = await tauri.event.listen(
-            'compile-progress',
-            (event: { payload: CompileProgressEvent }) => {
-              const { stage, percent } = event.payload;
-              onProgress(stage as 'compiling' | 'uploading', percent);
-            }
-          );
-        }
+        unlisten = await listen<JobProgressEvent>('job-progress', (event) => {
+          if (event.job_id !== jobId) return;
+          onProgress(event.stage, event.percent, event.message);
+        });
       }
 
-      // Call the Rust upload_code command
-      const result = await invoke<UploadResult>('upload_code', {
-        port,
-        code,
-        board,
-      });
-
-      return result;
+      return await invoke<UploadResult>(command, { ...args, jobId });
     } catch (error) {
       console.error('Upload failed:', error);
-      return {
-        success: false,
-        error: error instanceof Error ? error.message : String(error),
-      };
+      return failedResult(error);
     } finally {
       // Clean up event listener
       if (unlisten) {
@@ -304,7 +384,7 @@ export class TauriAdapter implements DeviceAdapter {
    * Install an Arduino core
    */
   async installCore(coreId: string): Promise<string> {
-    return await invoke<string>('install_core', { coreId });
+    return await invoke<string>('install_core', { coreId, jobId: newJobId('install') });
   }
 
   /**
@@ -335,6 +415,344 @@ export class TauriAdapter implements DeviceAdapter {
     }
   }
 
+  /**
+   * Detect boards connected to a port from its USB VID/PID
+   */
+  async detectBoard(port: string): Promise<BoardCandidate[]> {
+    try {
+      return await invoke<BoardCandidate[]>('detect_board', { port });
+    } catch {
+      return [];
+    }
+  }
+
+  /**
+   * List installed libraries
+   */
+  async listLibraries(): Promise<LibraryInfo[]> {
+    try {
+      return await invoke<LibraryInfo[]>('list_libraries');
+    } catch {
+      return [];
+    }
+  }
+
+  /**
+   * Search the library index
+   */
+  async searchLibraries(query: string): Promise<LibraryInfo[]> {
+    try {
+      return await invoke<LibraryInfo[]>('search_libraries', { query });
+    } catch {
+      return [];
+    }
+  }
+
+  /**
+   * Install a library, latest version unless one is given
+   */
+  async installLibrary(name: string, version?: string): Promise<string> {
+    return await invoke<string>('install_library', {
+      name,
+      version: version ?? null,
+      jobId: newJobId('install'),
+    });
+  }
+
+  /**
+   * Upgrade a library, or every installed library without a name
+   */
+  async upgradeLibrary(name?: string): Promise<string> {
+    return await invoke<string>('upgrade_library', { name: name ?? null, jobId: newJobId('install') });
+  }
+
+  /**
+   * Uninstall a library
+   */
+  async uninstallLibrary(name: string): Promise<string> {
+    return await invoke<string>('uninstall_library', { name, jobId: newJobId('install') });
+  }
+
+  /**
+   * Pick a library .zip (or folder) with the native dialog
+   */
+  async pickLibrary(folder = false): Promise<string | null> {
+    return await invoke<string | null>('pick_library_dialog', { folder });
+  }
+
+  /**
+   * Install a library from a local .zip or folder
+   */
+  async installLocalLibrary(path: string, replace = false): Promise<LocalLibrary> {
+    return await invoke<LocalLibrary>('install_local_library', { path, replace });
+  }
+
+  /**
+   * Find the sketch's includes that no installed library provides
+   */
+  async checkIncludes(code: string, board: string): Promise<MissingInclude[]> {
+    try {
+      return await invoke<MissingInclude[]>('check_includes', { code, board });
+    } catch {
+      return [];
+    }
+  }
+
+  /**
+   * Get list of bundled libraries
+   */
+  async getBundledLibraries(): Promise<BundledLibrary[]> {
+    try {
+      return await invoke<BundledLibrary[]>('get_bundled_libraries');
+    } catch {
+      return [];
+    }
+  }
+
+  /**
+   * Open a port and stream its output as "serial-data" events
+   */
+  async openSerial(port: string, settings: MonitorSettings): Promise<void> {
+    await invoke<void>('open_serial', { port, settings });
+  }
+
+  /**
+   * Close a port opened with openSerial
+   */
+  async closeSerial(port: string): Promise<void> {
+    await invoke<void>('close_serial', { port });
+  }
+
+  /**
+   * Send text to an open port, followed by its line ending
+   */
+  async writeSerial(port: string, data: string): Promise<void> {
+    await invoke<void>('write_serial', { port, data });
+  }
+
+  /**
+   * Listen to the data received on every open port
+   */
+  async onSerialData(handler: (event: SerialDataEvent) => void): Promise<Unlisten> {
+    return await listen<SerialDataEvent>('serial-data', handler);
+  }
+
+  /**
+   * Listen to monitor sessions closing
+   */
+  async onSerialClosed(handler: (event: SerialClosedEvent) => void): Promise<Unlisten> {
+    return await listen<SerialClosedEvent>('serial-closed', handler);
+  }
+
+  /**
+   * Listen to monitor sessions paused while an upload uses their port
+   */
+  async onSerialPaused(handler: (event: SerialPausedEvent) => void): Promise<Unlisten> {
+    return await listen<SerialPausedEvent>('serial-paused', handler);
+  }
+
+  /**
+   * Listen to monitor sessions reopened after an upload
+   */
+  async onSerialResumed(handler: (event: SerialResumedEvent) => void): Promise<Unlisten> {
+    return await listen<SerialResumedEvent>('serial-resumed', handler);
+  }
+
+  /**
+   * Set the DTR and/or RTS lines of an open port
+   */
+  async setSerialSignals(port: string, signals: { dtr?: boolean; rts?: boolean }): Promise<void> {
+    await invoke<void>('set_serial_signals', {
+      port,
+      dtr: signals.dtr ?? null,
+      rts: signals.rts ?? null,
+    });
+  }
+
+  /**
+   * Reset the board on a port by toggling DTR/RTS
+   */
+  async resetBoard(port: string, method?: ResetMethod): Promise<void> {
+    await invoke<void>('reset_board', { port, method: method ?? null });
+  }
+
+  /**
+   * 1200-baud touch, returns the bootloader port
+   */
+  async touch1200bps(port: string, board: string): Promise<string> {
+    return await invoke<string>('touch_1200bps', { port, board });
+  }
+
+  /**
+   * Start plotting the numeric output of an open port as "serial-plot" frames
+   */
+  async startPlotter(port: string, settings?: PlotterSettings): Promise<void> {
+    await invoke<void>('start_plotter', { port, settings: settings ?? null });
+  }
+
+  /**
+   * Stop plotting, the port stays open
+   */
+  async stopPlotter(port: string): Promise<void> {
+    await invoke<void>('stop_plotter', { port });
+  }
+
+  /**
+   * Listen to plot frames of every plotted port
+   */
+  async onSerialPlot(handler: (event: PlotFrameEvent) => void): Promise<Unlisten> {
+    return await listen<PlotFrameEvent>('serial-plot', handler);
+  }
+
+  /**
+   * Record the lines received on an open port, returns the file path
+   */
+  async startRecording(port: string, format?: RecordFormat, path?: string): Promise<string> {
+    return await invoke<string>('start_recording', {
+      port,
+      format: format ?? null,
+      path: path ?? null,
+    });
+  }
+
+  /**
+   * Stop recording and close the file
+   */
+  async stopRecording(port: string): Promise<RecordingSummary> {
+    return await invoke<RecordingSummary>('stop_recording', { port });
+  }
+
+  /**
+   * Replay a recording through the "serial-data" / "serial-plot" events
+   */
+  async replayRecording(path: string, speed?: number, plot?: PlotterSettings): Promise<string> {
+    return await invoke<string>('replay_recording', {
+      path,
+      speed: speed ?? null,
+      plot: plot ?? null,
+    });
+  }
+
+  /**
+   * Stop a running replay
+   */
+  async stopReplay(port: string): Promise<void> {
+    await invoke<void>('stop_replay', { port });
+  }
+
+  /**
+   * Guess the baud rate a board prints at
+   */
+  async detectBaudRate(port: string, sampleMs?: number): Promise<BaudDetection> {
+    return await invoke<BaudDetection>('detect_baud_rate', { port, sampleMs: sampleMs ?? null });
+  }
+
+  /**
+   * Baud rate of the sketch's Serial.begin(N)
+   */
+  async sketchBaudRate(code: string): Promise<number | null> {
+    try {
+      return await invoke<number | null>('sketch_baud_rate', { code });
+    } catch {
+      return null;
+    }
+  }
+
+  /**
+   * Cancel a running compile/upload/install
+   */
+  async cancelJob(jobId: string): Promise<void> {
+    await invoke<void>('cancel_job', { jobId });
+  }
+
+  /**
+   * IDs of the running jobs
+   */
+  async listJobs(): Promise<string[]> {
+    try {
+      return await invoke<string[]>('list_jobs');
+    } catch {
+      return [];
+    }
+  }
+
+  /**
+   * Get the per-process job timeouts
+   */
+  async getJobTimeouts(): Promise<JobTimeouts> {
+    return await invoke<JobTimeouts>('get_job_timeouts');
+  }
+
+  /**
+   * Set the per-process job timeouts
+   */
+  async setJobTimeouts(timeouts: JobTimeouts): Promise<void> {
+    await invoke<void>('set_job_timeouts', { timeouts });
+  }
+
+  /**
+   * Listen to the progress of every job
+   */
+  async onJobProgress(handler: (event: JobProgressEvent) => void): Promise<Unlisten> {
+    return await listen<JobProgressEvent>('job-progress', handler);
+  }
+
+  /**
+   * Listen to the output lines of every job process
+   */
+  async onBuildLog(handler: (event: BuildLogEvent) => void): Promise<Unlisten> {
+    return await listen<BuildLogEvent>('build-log', handler);
+  }
+
+  /**
+   * Break down the flash/RAM usage of the last build per symbol
+   */
+  async symbolBreakdown(): Promise<SymbolReport> {
+    return await invoke<SymbolReport>('symbol_breakdown');
+  }
+
+  /**
+   * Preprocessed sketch of the last build
+   */
+  async preprocessedSource(): Promise<string> {
+    return await invoke<string>('preprocessed_source');
+  }
+
+  /**
+   * Disassemble the last build, one entry per function
+   */
+  async disassemble(): Promise<DisassembledFunction[]> {
+    return await invoke<DisassembledFunction[]>('disassemble');
+  }
+
+  /**
+   * List the build directories, most recent first
+   */
+  async listBuilds(): Promise<BuildEntry[]> {
+    try {
+      return await invoke<BuildEntry[]>('list_builds');
+    } catch {
+      return [];
+    }
+  }
+
+  /**
+   * Disk usage of the builds and the shared core cache
+   */
+  async getBuildsSize(): Promise<BuildsSize> {
+    return await invoke<BuildsSize>('get_builds_size');
+  }
+
+  /**
+   * Delete builds not compiled in `olderThanDays`, all without it
+   */
+  async purgeBuilds(olderThanDays?: number, includeCache = false): Promise<PurgeSummary> {
+    return await invoke<PurgeSummary>('purge_builds', {
+      olderThanDays: olderThanDays ?? null,
+      includeCache,
+    });
+  }
+
   /**
    * Export project file using native save dialog
    */
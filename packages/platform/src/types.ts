@@ -7,9 +7,194 @@
  */
 export interface SerialPort {
   path: string;
+  portType?: 'usb' | 'pci' | 'bluetooth' | 'unknown';
   manufacturer?: string;
+  product?: string;
+  serialNumber?: string;
   vendorId?: string;
   productId?: string;
+  interface?: number;
+}
+
+/**
+ * Serial monitor settings, same options as the Arduino IDE monitor
+ */
+export interface MonitorSettings {
+  baud_rate: number;
+  /** 5-8, default 8 */
+  data_bits?: number;
+  parity?: 'none' | 'odd' | 'even';
+  /** 1 or 2, default 1 */
+  stop_bits?: number;
+  /** Appended to every write, default 'nl' */
+  line_ending?: 'none' | 'nl' | 'cr' | 'crlf';
+}
+
+/**
+ * Data received from an open port ("serial-data" event)
+ */
+export interface SerialDataEvent {
+  port: string;
+  data: string;
+}
+
+/**
+ * Monitor session closed ("serial-closed" event)
+ */
+export interface SerialClosedEvent {
+  port: string;
+  /** Set when the port went away, e.g. board unplugged */
+  error: string | null;
+}
+
+/**
+ * Stops listening to a backend event
+ */
+export type Unlisten = () => void;
+
+/**
+ * Monitor session paused while an upload uses the port ("serial-paused" event)
+ */
+export interface SerialPausedEvent {
+  port: string;
+}
+
+/**
+ * Monitor session reopened after an upload ("serial-resumed" event)
+ */
+export interface SerialResumedEvent {
+  port: string;
+  /** Differs from port when the board re-enumerated at a new path */
+  previous_port: string;
+}
+
+/**
+ * How to reset a board through the USB-serial control lines
+ * 'dtr': Uno/Nano/Mega auto-reset, 'esp': ESP32/ESP8266 EN through RTS
+ */
+export type ResetMethod = 'dtr' | 'esp';
+
+/**
+ * Serial plotter options
+ */
+export interface PlotterSettings {
+  /** Frames emitted per second, default 30 */
+  rate_hz?: number;
+  /** Points kept per series in each frame, default 200 */
+  max_points?: number;
+}
+
+/**
+ * Samples received since the previous frame ("serial-plot" event)
+ */
+export interface PlotFrameEvent {
+  port: string;
+  /** Numeric lines received in this frame, before decimation */
+  samples: number;
+  series: Array<{
+    name: string;
+    values: number[];
+  }>;
+}
+
+/**
+ * Recording file format: raw log, CSV or JSON lines
+ */
+export type RecordFormat = 'log' | 'csv' | 'jsonl';
+
+/**
+ * Result of a finished recording
+ */
+export interface RecordingSummary {
+  path: string;
+  lines: number;
+  duration_ms: number;
+}
+
+/**
+ * Result of a baud rate detection
+ */
+export interface BaudDetection {
+  /** null if nothing looked like text */
+  baud_rate: number | null;
+  scores: Array<{
+    baud_rate: number;
+    bytes: number;
+    /** 0-1, share of printable bytes */
+    score: number;
+  }>;
+}
+
+/**
+ * One sized symbol of a build
+ */
+export interface BuildSymbol {
+  /** Demangled name */
+  name: string;
+  section: 'text' | 'data' | 'bss' | 'rodata';
+  size: number;
+  file: string | null;
+  line: number | null;
+}
+
+/**
+ * Flash/RAM breakdown of the last build, groups largest first
+ */
+export interface SymbolReport {
+  elf: string;
+  /** Section totals from avr-size */
+  sections: Array<{ name: string; size: number }>;
+  groups: Array<{
+    origin: 'sketch' | 'core' | 'library' | 'other';
+    /** "sketch", "core", "other", or the library name */
+    name: string;
+    /** Bytes of program storage (text + data) */
+    flash: number;
+    /** Bytes of RAM (data + bss) */
+    ram: number;
+    /** Largest first */
+    symbols: BuildSymbol[];
+  }>;
+}
+
+/**
+ * Disassembly of one function of a build, source lines interleaved
+ */
+export interface DisassembledFunction {
+  /** Demangled name */
+  name: string;
+  address: string;
+  code: string;
+}
+
+/**
+ * A persistent build directory, one per project and board
+ */
+export interface BuildEntry {
+  project: string;
+  fqbn: string;
+  path: string;
+  /** Bytes on disk */
+  size: number;
+  /** Last compile, milliseconds since the Unix epoch */
+  modified_ms: number;
+}
+
+/**
+ * Bytes used by the build directories and the shared core cache
+ */
+export interface BuildsSize {
+  builds: number;
+  cache: number;
+}
+
+/**
+ * Result of a build purge
+ */
+export interface PurgeSummary {
+  removed: number;
+  /** Bytes freed */
+  freed: number;
 }
 
 /**
@@ -22,6 +207,46 @@ export interface CoreInfo {
   name: string;
 }
 
+/**
+ * Arduino library information
+ */
+export interface LibraryInfo {
+  name: string;
+  /** Installed version, empty if not installed */
+  installed: string;
+  /** Latest version in the index, empty if unknown */
+  latest: string;
+  author: string;
+  sentence: string;
+  /** Headers the library provides */
+  includes: string[];
+}
+
+/**
+ * Result of installing a library from a local .zip or folder
+ */
+export interface LocalLibrary {
+  name: string;
+  /** From library.properties, empty without one */
+  version: string;
+  includes: string[];
+  path: string;
+  /** Version already installed, null if the library is new */
+  previous: string | null;
+  /** False when a version was already installed and replace wasn't set */
+  installed: boolean;
+}
+
+/**
+ * Library that ships with the desktop app
+ */
+export interface BundledLibrary {
+  name: string;
+  /** Pinned version, null when the latest was bundled */
+  version: string | null;
+  includes: string[];
+}
+
 /**
  * Arduino board information
  */
@@ -30,6 +255,16 @@ export interface BoardInfo {
   fqbn: string;
 }
 
+/**
+ * Board that may be connected to a serial port
+ */
+export interface BoardCandidate {
+  fqbn: string;
+  name: string;
+  confidence: number;
+  source: 'arduino-cli' | 'usb-table';
+}
+
 /**
  * Core installation status
  */
@@ -39,6 +274,111 @@ export interface CoreStatus {
   bundled: boolean;
 }
 
+/**
+ * Compiler diagnostic (file is "sketch.ino" for the sketch itself)
+ */
+export interface CompilerDiagnostic {
+  file: string;
+  line: number | null;
+  column: number | null;
+  severity: 'error' | 'warning' | 'note';
+  message: string;
+  notes: Array<{
+    file: string;
+    line: number | null;
+    column: number | null;
+    message: string;
+  }>;
+  /** Blocks that generated the line, innermost first */
+  block_ids: string[];
+}
+
+/**
+ * Sketch line range (1-based, inclusive) generated by a Blockly block
+ */
+export interface SourceMapEntry {
+  start_line: number;
+  end_line: number;
+  block_id: string;
+}
+
+/**
+ * Optional settings of compile, upload and uploadBuild
+ */
+export interface CompileOptions {
+  /** Project the sketch belongs to, each project + board keeps its build */
+  project?: string;
+  /** Line ranges of the blocks, to map diagnostics to blocks */
+  source_map?: SourceMapEntry[];
+  /** Language of error hints, e.g. "fr" or "fr-FR" ("en" by default) */
+  lang?: string;
+  /** Flag flash/RAM usage at or above this percentage (90 by default) */
+  memory_warning_percent?: number;
+}
+
+/**
+ * Beginner-friendly explanation of a known compile/upload error
+ */
+export interface ErrorHint {
+  id: string;
+  explanation: string;
+  fix: string;
+}
+
+/**
+ * Flash or RAM usage of a build
+ */
+export interface MemoryUsage {
+  used: number;
+  max: number | null;
+  percent: number | null;
+  /** Usage is at or above the warning threshold */
+  warning: boolean;
+}
+
+/**
+ * Report of a successful compile
+ */
+export interface CompileReport {
+  /** Pass to the upload_build command to upload without recompiling */
+  build_id: string;
+  build_path: string;
+  flash: MemoryUsage | null;
+  ram: MemoryUsage | null;
+  warning_percent: number;
+}
+
+/**
+ * Summary of a finished upload
+ */
+export interface UploadReport {
+  /** null when the uploader doesn't print it */
+  bytes_written: number | null;
+  /** null when the uploader didn't verify */
+  verified: boolean | null;
+  elapsed_ms: number;
+}
+
+/**
+ * Output line of an arduino-cli process ("build-log" event)
+ */
+export interface BuildLogEvent {
+  job_id: string;
+  stream: 'stdout' | 'stderr';
+  line: string;
+  /** Milliseconds since the Unix epoch */
+  timestamp_ms: number;
+}
+
+/**
+ * Included header that no installed library provides
+ */
+export interface MissingInclude {
+  header: string;
+  /** Libraries that provide it, bundled ones first */
+  candidates: string[];
+}
+
 /**
  * Upload result from Arduino compilation/upload
  */
@@ -47,6 +387,11 @@ export interface UploadResult {
   stage?: 'compile' | 'upload';
   message?: string;
   error?: string;
+  diagnostics?: CompilerDiagnostic[];
+  hints?: ErrorHint[];
+  missing_include?: MissingInclude | null;
+  report?: CompileReport | null;
+  upload?: UploadReport | null;
 }
 
 /**
@@ -59,12 +404,44 @@ export interface PlatformCapabilities {
   supportsProgress: boolean;
 }
 
+/**
+ * Stage of a compile/upload/install job, as reported by the backend
+ */
+export type JobStage =
+  | 'initializing'
+  | 'compiling'
+  | 'linking'
+  | 'uploading'
+  | 'installing'
+  | 'done'
+  | 'failed';
+
+/**
+ * Job progress from the backend ("job-progress" event)
+ */
+export interface JobProgressEvent {
+  job_id: string;
+  stage: JobStage;
+  percent: number;
+  message: string;
+}
+
+/**
+ * Per-process timeouts of jobs in seconds, 0 disables the timeout
+ */
+export interface JobTimeouts {
+  compile_secs: number;
+  upload_secs: number;
+  install_secs: number;
+}
+
 /**
  * Upload progress callback type
  */
 export type UploadProgressCallback = (
-  stage: 'compiling' | 'uploading',
-  percent: number
+  stage: JobStage,
+  percent: number,
+  message?: string
 ) => void;
 
 /**
@@ -84,15 +461,23 @@ export interface DeviceAdapter {
 
   /**
    * List available serial ports
+   * @param includeAll Also list non-USB ports (Bluetooth, PCI, virtual)
    */
-  listPorts(): Promise<SerialPort[]>;
+  listPorts(includeAll?: boolean): Promise<SerialPort[]>;
 
   /**
    * Verify/compile code without uploading
    * @param code Arduino C++ code
    * @param board Board type (uno, nano, mega, etc.)
+   * @param options Project, source map, hint language and memory warning level
+   * @param jobId ID to cancel the compile with cancelJob, generated if omitted
    */
-  compile(code: string, board: string): Promise<UploadResult>;
+  compile(
+    code: string,
+    board: string,
+    options?: CompileOptions,
+    jobId?: string
+  ): Promise<UploadResult>;
 
   /**
    * Upload code to Arduino
@@ -100,12 +485,32 @@ export interface DeviceAdapter {
    * @param code Arduino C++ code
    * @param board Board type (uno, nano, mega, etc.)
    * @param onProgress Optional progress callback
+   * @param options Project, source map, hint language and memory warning level
+   * @param jobId ID to cancel the upload with cancelJob, generated if omitted
    */
   upload(
     port: string,
     code: string,
     board: string,
-    onProgress?: UploadProgressCallback
+    onProgress?: UploadProgressCallback,
+    options?: CompileOptions,
+    jobId?: string
+  ): Promise<UploadResult>;
+
+  /**
+   * Upload a build made by compile or upload without recompiling
+   * @param buildId build_id of the compile's report
+   * @param port Serial port path
+   * @param onProgress Optional progress callback
+   * @param options Hint language of upload errors
+   * @param jobId ID to cancel the upload with cancelJob, generated if omitted
+   */
+  uploadBuild(
+    buildId: string,
+    port: string,
+    onProgress?: UploadProgressCallback,
+    options?: CompileOptions,
+    jobId?: string
   ): Promise<UploadResult>;
 
   /**
@@ -162,4 +567,227 @@ export interface DeviceAdapter {
    * Get list of bundled cores
    */
   getBundledCores(): Promise<string[]>;
+
+  /**
+   * Detect boards that may be connected to a port, most likely first
+   * @param port Serial port path
+   */
+  detectBoard(port: string): Promise<BoardCandidate[]>;
+
+  /**
+   * List installed libraries
+   */
+  listLibraries(): Promise<LibraryInfo[]>;
+
+  /**
+   * Search the library index
+   */
+  searchLibraries(query: string): Promise<LibraryInfo[]>;
+
+  /**
+   * Install a library, latest version unless one is given
+   */
+  installLibrary(name: string, version?: string): Promise<string>;
+
+  /**
+   * Upgrade a library, or every installed library without a name
+   */
+  upgradeLibrary(name?: string): Promise<string>;
+
+  /**
+   * Uninstall a library
+   */
+  uninstallLibrary(name: string): Promise<string>;
+
+  /**
+   * Pick a library .zip (or folder) with the native dialog, null if cancelled
+   */
+  pickLibrary(folder?: boolean): Promise<string | null>;
+
+  /**
+   * Install a library from a local .zip or folder
+   * @param replace Replace an installed version of the same library
+   */
+  installLocalLibrary(path: string, replace?: boolean): Promise<LocalLibrary>;
+
+  /**
+   * Find the sketch's includes that no installed library provides
+   */
+  checkIncludes(code: string, board: string): Promise<MissingInclude[]>;
+
+  /**
+   * Get list of bundled libraries
+   */
+  getBundledLibraries(): Promise<BundledLibrary[]>;
+
+  // ========== Serial Monitor (Desktop only) ==========
+
+  /**
+   * Open a port and stream its output as serial data events
+   */
+  openSerial(port: string, settings: MonitorSettings): Promise<void>;
+
+  /**
+   * Close a port opened with openSerial
+   */
+  closeSerial(port: string): Promise<void>;
+
+  /**
+   * Send text to an open port, followed by its line ending
+   */
+  writeSerial(port: string, data: string): Promise<void>;
+
+  /**
+   * Listen to the data received on every open port
+   */
+  onSerialData(handler: (event: SerialDataEvent) => void): Promise<Unlisten>;
+
+  /**
+   * Listen to monitor sessions closing
+   */
+  onSerialClosed(handler: (event: SerialClosedEvent) => void): Promise<Unlisten>;
+
+  /**
+   * Listen to monitor sessions paused while an upload uses their port
+   */
+  onSerialPaused(handler: (event: SerialPausedEvent) => void): Promise<Unlisten>;
+
+  /**
+   * Listen to monitor sessions reopened after an upload
+   */
+  onSerialResumed(handler: (event: SerialResumedEvent) => void): Promise<Unlisten>;
+
+  /**
+   * Set the DTR and/or RTS lines of an open port, omitted lines are left as is
+   */
+  setSerialSignals(port: string, signals: { dtr?: boolean; rts?: boolean }): Promise<void>;
+
+  /**
+   * Reset the board on a port by toggling DTR/RTS
+   */
+  resetBoard(port: string, method?: ResetMethod): Promise<void>;
+
+  /**
+   * 1200-baud touch to enter the bootloader of native USB boards
+   * @param board Board FQBN, boards that don't upload with a touch are left alone
+   * A monitor open on the port is closed, reopen it once the upload is done
+   * @returns The bootloader port, which often has a different path
+   */
+  touch1200bps(port: string, board: string): Promise<string>;
+
+  /**
+   * Start plotting the numeric output of an open port
+   */
+  startPlotter(port: string, settings?: PlotterSettings): Promise<void>;
+
+  /**
+   * Stop plotting, the port stays open
+   */
+  stopPlotter(port: string): Promise<void>;
+
+  /**
+   * Listen to plot frames of every plotted port
+   */
+  onSerialPlot(handler: (event: PlotFrameEvent) => void): Promise<Unlisten>;
+
+  /**
+   * Record the lines received on an open port with their timestamps
+   * @param path File to write, defaults to the app data dir
+   * @returns The file path
+   */
+  startRecording(port: string, format?: RecordFormat, path?: string): Promise<string>;
+
+  /**
+   * Stop recording and close the file, the port stays open
+   */
+  stopRecording(port: string): Promise<RecordingSummary>;
+
+  /**
+   * Replay a recording as serial data (and plot frames with `plot`)
+   * @param speed Scales the recorded timing, 0 replays as fast as possible
+   * @returns The virtual port the replay is sent on ("replay:<file name>")
+   */
+  replayRecording(path: string, speed?: number, plot?: PlotterSettings): Promise<string>;
+
+  /**
+   * Stop a running replay
+   */
+  stopReplay(port: string): Promise<void>;
+
+  /**
+   * Guess the baud rate a board prints at by sampling every common rate
+   * @param sampleMs Sample window per rate
+   */
+  detectBaudRate(port: string, sampleMs?: number): Promise<BaudDetection>;
+
+  /**
+   * Baud rate of the sketch's Serial.begin(N), null without one
+   */
+  sketchBaudRate(code: string): Promise<number | null>;
+
+  // ========== Jobs (Desktop only) ==========
+
+  /**
+   * Cancel a running compile/upload/install, killing its processes
+   */
+  cancelJob(jobId: string): Promise<void>;
+
+  /**
+   * IDs of the running jobs
+   */
+  listJobs(): Promise<string[]>;
+
+  /**
+   * Get the per-process job timeouts
+   */
+  getJobTimeouts(): Promise<JobTimeouts>;
+
+  /**
+   * Set the per-process job timeouts, applies to jobs started afterwards
+   */
+  setJobTimeouts(timeouts: JobTimeouts): Promise<void>;
+
+  /**
+   * Listen to the progress of every job
+   */
+  onJobProgress(handler: (event: JobProgressEvent) => void): Promise<Unlisten>;
+
+  /**
+   * Listen to the output lines of every job process
+   */
+  onBuildLog(handler: (event: BuildLogEvent) => void): Promise<Unlisten>;
+
+  // ========== Build Inspection (Desktop only) ==========
+
+  /**
+   * Break down the flash/RAM usage of the last build per symbol
+   */
+  symbolBreakdown(): Promise<SymbolReport>;
+
+  /**
+   * Preprocessed sketch of the last build (.ino.cpp with its prototypes)
+   */
+  preprocessedSource(): Promise<string>;
+
+  /**
+   * Disassemble the last build, one entry per function in address order
+   */
+  disassemble(): Promise<DisassembledFunction[]>;
+
+  /**
+   * List the build directories, most recent first
+   */
+  listBuilds(): Promise<BuildEntry[]>;
+
+  /**
+   * Disk usage of the builds and the shared core cache
+   */
+  getBuildsSize(): Promise<BuildsSize>;
+
+  /**
+   * Delete builds not compiled in `olderThanDays` (all without it)
+   * Builds in use are kept
+   * @param includeCache Also clear the shared core cache when no build runs
+   */
+  purgeBuilds(olderThanDays?: number, includeCache?: boolean): Promise<PurgeSummary>;
 }
@@ -6,7 +6,33 @@ import type {
   UploadProgressCallback,
   CoreInfo,
   BoardInfo,
+  BoardCandidate,
   CoreStatus,
+  LibraryInfo,
+  BundledLibrary,
+  MissingInclude,
+  LocalLibrary,
+  CompileOptions,
+  MonitorSettings,
+  SerialDataEvent,
+  SerialClosedEvent,
+  Unlisten,
+  SerialPausedEvent,
+  SerialResumedEvent,
+  ResetMethod,
+  PlotterSettings,
+  PlotFrameEvent,
+  RecordFormat,
+  RecordingSummary,
+  BaudDetection,
+  JobProgressEvent,
+  JobTimeouts,
+  BuildLogEvent,
+  SymbolReport,
+  DisassembledFunction,
+  BuildEntry,
+  BuildsSize,
+  PurgeSummary,
 } from './types';
 
 
@@ -82,6 +108,19 @@ export class WebAdapter implements DeviceAdapter {
     };
   }
 
+  async uploadBuild(
+    _buildId: string,
+    _port: string,
+    _onProgress?: UploadProgressCallback,
+    _options?: CompileOptions,
+    _jobId?: string
+  ): Promise<UploadResult> {
+    return {
+      success: false,
+      error: 'Upload not supported in browser. Please use the desktop app or a cloud service.',
+    };
+  }
+
   /**
    * Export project file using Download API
    */
@@ -163,4 +202,174 @@ export class WebAdapter implements DeviceAdapter {
   async getBundledCores(): Promise<string[]> {
     return [];
   }
+
+  async detectBoard(_port: string): Promise<BoardCandidate[]> {
+    return [];
+  }
+
+  async listLibraries(): Promise<LibraryInfo[]> {
+    return [];
+  }
+
+  async searchLibraries(_query: string): Promise<LibraryInfo[]> {
+    return [];
+  }
+
+  async installLibrary(_name: string, _version?: string): Promise<string> {
+    throw new Error('Library installation not available in browser. Please use the desktop app.');
+  }
+
+  async upgradeLibrary(_name?: string): Promise<string> {
+    throw new Error('Library installation not available in browser. Please use the desktop app.');
+  }
+
+  async uninstallLibrary(_name: string): Promise<string> {
+    throw new Error('Library installation not available in browser. Please use the desktop app.');
+  }
+
+  async pickLibrary(_folder?: boolean): Promise<string | null> {
+    return null;
+  }
+
+  async installLocalLibrary(_path: string, _replace?: boolean): Promise<LocalLibrary> {
+    throw new Error('Library installation not available in browser. Please use the desktop app.');
+  }
+
+  async checkIncludes(_code: string, _board: string): Promise<MissingInclude[]> {
+    return [];
+  }
+
+  async getBundledLibraries(): Promise<BundledLibrary[]> {
+    return [];
+  }
+
+  // ========== Serial Monitor (Not available in browser) ==========
+
+  async openSerial(_port: string, _settings: MonitorSettings): Promise<void> {
+    throw new Error('Serial monitor not available in browser. Please use the desktop app.');
+  }
+
+  async closeSerial(_port: string): Promise<void> {}
+
+  async writeSerial(_port: string, _data: string): Promise<void> {
+    throw new Error('Serial monitor not available in browser. Please use the desktop app.');
+  }
+
+  async onSerialData(_handler: (event: SerialDataEvent) => void): Promise<Unlisten> {
+    return () => {};
+  }
+
+  async onSerialClosed(_handler: (event: SerialClosedEvent) => void): Promise<Unlisten> {
+    return () => {};
+  }
+
+  async onSerialPaused(_handler: (event: SerialPausedEvent) => void): Promise<Unlisten> {
+    return () => {};
+  }
+
+  async onSerialResumed(_handler: (event: SerialResumedEvent) => void): Promise<Unlisten> {
+    return () => {};
+  }
+
+  async setSerialSignals(_port: string, _signals: { dtr?: boolean; rts?: boolean }): Promise<void> {
+    throw new Error('Serial monitor not available in browser. Please use the desktop app.');
+  }
+
+  async resetBoard(_port: string, _method?: ResetMethod): Promise<void> {
+    throw new Error('Board reset not available in browser. Please use the desktop app.');
+  }
+
+  async touch1200bps(_port: string, _board: string): Promise<string> {
+    throw new Error('Board reset not available in browser. Please use the desktop app.');
+  }
+
+  async startPlotter(_port: string, _settings?: PlotterSettings): Promise<void> {
+    throw new Error('Serial plotter not available in browser. Please use the desktop app.');
+  }
+
+  async stopPlotter(_port: string): Promise<void> {}
+
+  async onSerialPlot(_handler: (event: PlotFrameEvent) => void): Promise<Unlisten> {
+    return () => {};
+  }
+
+  async startRecording(_port: string, _format?: RecordFormat, _path?: string): Promise<string> {
+    throw new Error('Serial recording not available in browser. Please use the desktop app.');
+  }
+
+  async stopRecording(_port: string): Promise<RecordingSummary> {
+    throw new Error('Serial recording not available in browser. Please use the desktop app.');
+  }
+
+  async replayRecording(_path: string, _speed?: number, _plot?: PlotterSettings): Promise<string> {
+    throw new Error('Serial recording not available in browser. Please use the desktop app.');
+  }
+
+  async stopReplay(_port: string): Promise<void> {}
+
+  async detectBaudRate(_port: string, _sampleMs?: number): Promise<BaudDetection> {
+    throw new Error('Baud rate detection not available in browser. Please use the desktop app.');
+  }
+
+  /**
+   * Same rule as the desktop backend: the first Serial.begin(N) outside a
+   * comment, mySerial.begin and other instances are skipped
+   */
+  async sketchBaudRate(code: string): Promise<number | null> {
+    for (const line of code.split('\n')) {
+      const match = line.split('//')[0].match(/(?<![\w])Serial\.begin\s*\(\s*(\d+)/);
+      if (match) {
+        return Number(match[1]);
+      }
+    }
+    return null;
+  }
+
+  // ========== Jobs (Not available in browser) ==========
+
+  async cancelJob(_jobId: string): Promise<void> {}
+
+  async listJobs(): Promise<string[]> {
+    return [];
+  }
+
+  async getJobTimeouts(): Promise<JobTimeouts> {
+    return { compile_secs: 0, upload_secs: 0, install_secs: 0 };
+  }
+
+  async setJobTimeouts(_timeouts: JobTimeouts): Promise<void> {}
+
+  async onJobProgress(_handler: (event: JobProgressEvent) => void): Promise<Unlisten> {
+    return () => {};
+  }
+
+  async onBuildLog(_handler: (event: BuildLogEvent) => void): Promise<Unlisten> {
+    return () => {};
+  }
+
+  // ========== Build Inspection (Not available in browser) ==========
+
+  async symbolBreakdown(): Promise<SymbolReport> {
+    throw new Error('Build inspection not available in browser. Please use the desktop app.');
+  }
+
+  async preprocessedSource(): Promise<string> {
+    throw new Error('Build inspection not available in browser. Please use the desktop app.');
+  }
+
+  async disassemble(): Promise<DisassembledFunction[]> {
+    throw new Error('Build inspection not available in browser. Please use the desktop app.');
+  }
+
+  async listBuilds(): Promise<BuildEntry[]> {
+    return [];
+  }
+
+  async getBuildsSize(): Promise<BuildsSize> {
+    return { builds: 0, cache: 0 };
+  }
+
+  async purgeBuilds(_olderThanDays?: number, _includeCache?: boolean): Promise<PurgeSummary> {
+    return { removed: 0, freed: 0 };
+  }
 }